@@ -0,0 +1,201 @@
+//! A uniform grid over all the tx comps in the world, rebuilt once per frame.
+//! Lets collision resolution only look at comps that are actually nearby instead of everything.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    bullet_time::{BulletTimeClass, BulletTimeGeneric},
+    config::PhysicsConfig,
+    dyno::Dyno,
+    hshape::HShape,
+    pos::Pos,
+    statics::{StaticRx, StaticTx},
+    triggers::{TriggerKind, TriggerRxGeneric, TriggerTxGeneric},
+};
+
+/// Identifies a single comp on a ctrl
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, std::hash::Hash)]
+pub(crate) struct CompKey {
+    pub(crate) eid: Entity,
+    pub(crate) ix: usize,
+}
+
+pub(crate) struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<CompKey>>,
+    /// Comps that move around while collisions are being resolved, so are returned by every query
+    always: Vec<CompKey>,
+}
+impl SpatialHash {
+    fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: default(),
+            always: vec![],
+        }
+    }
+
+    fn cell_range(&self, rect: Rect) -> (IVec2, IVec2) {
+        let min = rect.min / self.cell_size;
+        let max = rect.max / self.cell_size;
+        (min.floor().as_ivec2(), max.floor().as_ivec2())
+    }
+
+    fn clear(&mut self) {
+        // Keep the allocated vecs around, we're going to fill most of them right back up
+        for keys in self.cells.values_mut() {
+            keys.clear();
+        }
+        self.always.clear();
+    }
+
+    fn insert_rect(&mut self, key: CompKey, rect: Rect) {
+        let (min, max) = self.cell_range(rect);
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(key);
+            }
+        }
+    }
+
+    fn insert_always(&mut self, key: CompKey) {
+        self.always.push(key);
+    }
+
+    /// Returns the key of every comp that might overlap the given thbox.
+    /// NOTE: Sorted so that anything iterating over the result does so in a deterministic order
+    pub(crate) fn query(&self, thbox: &HShape) -> Vec<CompKey> {
+        self.query_rect(thbox.get_bounds())
+    }
+
+    /// Returns the key of every comp that might overlap the given rect, sorted
+    pub(crate) fn query_rect(&self, rect: Rect) -> Vec<CompKey> {
+        let (min, max) = self.cell_range(rect);
+        let mut result = self.always.clone();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                if let Some(keys) = self.cells.get(&IVec2::new(x, y)) {
+                    result.extend(keys.iter().copied());
+                }
+            }
+        }
+        result.sort();
+        result.dedup();
+        result
+    }
+
+    /// Returns each entity that has a comp that might overlap the given thbox, once
+    pub(crate) fn query_eids(&self, thbox: &HShape) -> Vec<Entity> {
+        let mut result = self
+            .query(thbox)
+            .into_iter()
+            .map(|key| key.eid)
+            .collect::<Vec<_>>();
+        result.dedup();
+        result
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct Broadphase {
+    pub(crate) statics: SpatialHash,
+    pub(crate) triggers: SpatialHash,
+    /// How far each trigger tx moved in one jump this frame (uninteresting dynos do this).
+    /// They're inserted covering their whole path so receivers can trigger on any of it.
+    pub(crate) trigger_sweeps: HashMap<Entity, Vec2>,
+}
+impl Broadphase {
+    fn new(cell_size: f32) -> Self {
+        Self {
+            statics: SpatialHash::new(cell_size),
+            triggers: SpatialHash::new(cell_size),
+            trigger_sweeps: default(),
+        }
+    }
+    /// How far the trigger tx moved in one jump this frame, zero if it didn't
+    pub(crate) fn get_trigger_sweep(&self, eid: Entity) -> Vec2 {
+        self.trigger_sweeps.get(&eid).copied().unwrap_or_default()
+    }
+    /// Adds a static tx comp where it is now, for txs spawned or moved since the last rebuild.
+    /// NOTE: Wherever it was before stays in too, that just means an extra candidate
+    pub(crate) fn refresh_static(&mut self, key: CompKey, thbox: &HShape) {
+        self.statics.insert_rect(key, thbox.get_bounds());
+    }
+    /// Same as `refresh_static`, for trigger tx comps
+    pub(crate) fn refresh_trigger(&mut self, key: CompKey, thbox: &HShape) {
+        self.triggers.insert_rect(key, thbox.get_bounds());
+    }
+}
+
+/// Rebuilds the broadphase from scratch. Should run once uninteresting dynos are done moving, but
+/// before static txs move. Those are inserted covering the whole path they're about to take.
+#[allow(clippy::type_complexity)]
+pub(crate) fn rebuild_broadphase<
+    TriggerRxKind: TriggerKind,
+    TriggerTxKind: TriggerKind,
+    TimeClass: BulletTimeClass,
+>(
+    mut broadphase: ResMut<Broadphase>,
+    config: Res<PhysicsConfig>,
+    bullet_time: Res<BulletTimeGeneric<TimeClass>>,
+    stx_q: Query<(Entity, &Pos, &StaticTx, Has<StaticRx>, Option<&Dyno>)>,
+    ttx_q: Query<(
+        Entity,
+        &Pos,
+        &TriggerTxGeneric<TriggerTxKind>,
+        Has<StaticRx>,
+        Has<TriggerRxGeneric<TriggerRxKind>>,
+        Option<&Dyno>,
+        Has<StaticTx>,
+    )>,
+) {
+    if broadphase.statics.cell_size != config.broadphase_cell_size {
+        let trigger_sweeps = std::mem::take(&mut broadphase.trigger_sweeps);
+        *broadphase = Broadphase::new(config.broadphase_cell_size);
+        broadphase.trigger_sweeps = trigger_sweeps;
+    }
+    // Static txs haven't moved yet, so their sweep runs backwards from where they'll end up
+    let get_static_sweep = |dyno: Option<&Dyno>| {
+        dyno.map(|dyno| -dyno.vel * bullet_time.delta_secs())
+            .unwrap_or_default()
+    };
+    broadphase.statics.clear();
+    for (eid, pos, stx, is_dynamic, dyno) in &stx_q {
+        let sweep = get_static_sweep(dyno);
+        for (ix, comp) in stx.comps.iter().enumerate() {
+            // Dynamic solids move with the interesting dynos, after this runs
+            if is_dynamic {
+                broadphase.statics.insert_always(CompKey { eid, ix });
+                continue;
+            }
+            let thbox = comp.shape.translated(pos.x, pos.y);
+            broadphase
+                .statics
+                .insert_rect(CompKey { eid, ix }, thbox.get_swept_rect(sweep));
+        }
+    }
+    broadphase.triggers.clear();
+    for (eid, pos, ttx, has_srx, has_trx, dyno, has_stx) in &ttx_q {
+        let sweep = if has_stx {
+            get_static_sweep(dyno)
+        } else {
+            broadphase.get_trigger_sweep(eid)
+        };
+        // Same as dynamic solids, anything moved with the interesting dynos isn't there yet
+        let is_interesting = has_srx || (has_trx && !has_stx);
+        for (ix, comp) in ttx.comps.iter().enumerate() {
+            if is_interesting {
+                broadphase.triggers.insert_always(CompKey { eid, ix });
+                continue;
+            }
+            let thbox = comp.shape.translated(pos.x, pos.y);
+            broadphase
+                .triggers
+                .insert_rect(CompKey { eid, ix }, thbox.get_swept_rect(sweep));
+        }
+    }
+}
+
+pub(super) fn register_broadphase(app: &mut App, cell_size: f32) {
+    app.insert_resource(Broadphase::new(cell_size));
+}
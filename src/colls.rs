@@ -1,7 +1,12 @@
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 
 use crate::{
+    config::DuplicateRecs,
     hbox::HBoxMarker,
+    material::PhysicsMaterial,
     pos::Pos,
     statics::{StaticRx, StaticRxKind, StaticTx, StaticTxKind},
     triggers::{TriggerKind, TriggerRxGeneric, TriggerTxGeneric},
@@ -13,6 +18,10 @@ pub type CollKey = u32;
 #[derive(Debug, Clone, Reflect)]
 pub struct StaticCollRec {
     pub push: Vec2,
+    /// Normal of the tx's surface where the rx hit it.
+    /// Usually the direction of `push`, but a slope reports the normal of its sloped side even
+    /// though it pushes the rx straight up.
+    pub normal: Vec2,
     /// Position of rx at time of collision
     pub rx_pos: Pos,
     /// Before collision, component of rx's velocity in collision normal direction
@@ -34,16 +43,58 @@ pub struct StaticCollRec {
     pub tx_kind: StaticTxKind,
     /// The marker of the hbox on the tx  triggering this collision
     pub tx_hbox: HBoxMarker,
+    /// The material of the tx comp. Already applied to the rx's velocity, here so gameplay code can
+    /// use the grip (or anything else about the surface)
+    pub tx_material: PhysicsMaterial,
+    /// How fast the tx's surface (like a conveyor belt) is carrying the rx along the contact
+    /// tangent. Zero for normal surfaces, and for anything that only observes.
+    pub surface_vel: Vec2,
+}
+/// Identifies a pair of comps (an rx comp and a tx comp), within a frame and across frames
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, std::hash::Hash)]
+pub(crate) struct CompPair {
+    pub(crate) rx_ctrl: Entity,
+    pub(crate) rx_ix: usize,
+    pub(crate) tx_ctrl: Entity,
+    pub(crate) tx_ix: usize,
 }
+
 #[derive(Resource, Debug, Reflect)]
 pub struct StaticColls {
     pub(crate) map: HashMap<CollKey, StaticCollRec>,
+    /// The key of the record for every pair with an observing rx comp so far this frame
+    #[reflect(ignore)]
+    pub(crate) observed: HashMap<CompPair, CollKey>,
+    /// Every pair that friction has been applied to so far this frame
+    #[reflect(ignore)]
+    pub(crate) rubbed: HashSet<CompPair>,
 }
 impl StaticColls {
     pub(crate) fn insert(&mut self, rec: StaticCollRec) {
         let key = self.map.len() as CollKey;
         self.map.insert(key, rec);
     }
+    /// Inserts the record for a pair with an observing rx comp. Only one record is made for each
+    /// pair each frame, `duplicate_recs` says whether later ones replace it.
+    pub(crate) fn insert_observed(
+        &mut self,
+        pair: CompPair,
+        rec: StaticCollRec,
+        duplicate_recs: DuplicateRecs,
+    ) {
+        if let Some(key) = self.observed.get(&pair) {
+            if duplicate_recs == DuplicateRecs::KeepLast {
+                self.map.insert(*key, rec);
+            }
+            return;
+        }
+        self.observed.insert(pair, self.map.len() as CollKey);
+        self.insert(rec);
+    }
+    /// Returns true the first time it's called for a pair each frame
+    pub(crate) fn rub(&mut self, pair: CompPair) -> bool {
+        self.rubbed.insert(pair)
+    }
     pub fn get(&self, key: &CollKey) -> Option<&StaticCollRec> {
         self.map.get(key)
     }
@@ -55,8 +106,72 @@ impl StaticColls {
     }
 }
 
+/// Records an rx being squeezed between two solids it could not be pushed out of
+#[derive(Debug, Clone, Reflect)]
+pub struct StaticCrushRec {
+    /// Position of rx at time of crush
+    pub rx_pos: Pos,
+    /// Entity of the control associated with the rx
+    pub rx_ctrl: Entity,
+    /// The marker of the hbox on the rx being crushed
+    pub rx_hbox: HBoxMarker,
+    /// Entity of the control associated with the tx that pushed the rx
+    pub pusher_ctrl: Entity,
+    /// The marker of the hbox on the tx that pushed the rx
+    pub pusher_hbox: HBoxMarker,
+    /// Entity of the control associated with the tx the rx was pushed into
+    pub blocker_ctrl: Entity,
+    /// The marker of the hbox on the tx the rx was pushed into
+    pub blocker_hbox: HBoxMarker,
+}
+impl StaticCrushRec {
+    fn is_same_crush(&self, rhs: &Self) -> bool {
+        self.rx_ctrl == rhs.rx_ctrl
+            && self.rx_hbox == rhs.rx_hbox
+            && self.pusher_ctrl == rhs.pusher_ctrl
+            && self.pusher_hbox == rhs.pusher_hbox
+            && self.blocker_ctrl == rhs.blocker_ctrl
+            && self.blocker_hbox == rhs.blocker_hbox
+    }
+}
+#[derive(Resource, Debug, Reflect)]
+pub struct StaticCrushes {
+    pub(crate) map: HashMap<CollKey, StaticCrushRec>,
+}
+impl StaticCrushes {
+    /// NOTE: The same crush is usually detected many times in a frame, only the first is kept
+    pub(crate) fn insert(&mut self, rec: StaticCrushRec) {
+        if self.map.values().any(|other| other.is_same_crush(&rec)) {
+            return;
+        }
+        let key = self.map.len() as CollKey;
+        self.map.insert(key, rec);
+    }
+    pub fn get(&self, key: &CollKey) -> Option<&StaticCrushRec> {
+        self.map.get(key)
+    }
+    pub fn get_refs(&self, crush_keys: &[CollKey]) -> Vec<&StaticCrushRec> {
+        crush_keys.iter().filter_map(|key| self.get(key)).collect()
+    }
+    pub fn all(&self) -> Vec<&StaticCrushRec> {
+        self.map.values().collect()
+    }
+}
+
+/// Whether a trigger collision is new this frame. When the comps stop overlapping, it shows up in
+/// `TriggerExitsGeneric` instead.
+#[derive(Clone, Copy, Debug, Reflect, PartialEq, Eq, std::hash::Hash)]
+pub enum TriggerPhase {
+    /// The comps weren't overlapping last frame
+    Enter,
+    /// The comps were overlapping last frame too
+    Stay,
+}
+
 #[derive(Debug, Clone, Reflect)]
 pub struct TriggerCollRecGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    /// Whether the comps have just started overlapping
+    pub phase: TriggerPhase,
     /// Position of rx at time of collision
     pub rx_pos: Pos,
     /// Entity of the control associated with the rx
@@ -74,9 +189,16 @@ pub struct TriggerCollRecGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: Trig
     /// The marker of the hbox on the tx triggering this collision
     pub tx_hbox: HBoxMarker,
 }
+
 #[derive(Resource, Debug, Reflect)]
 pub struct TriggerCollsGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
     pub(crate) map: HashMap<CollKey, TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>>,
+    /// Every pair that overlapped last frame, so we can tell enters from stays (and spot exits)
+    #[reflect(ignore)]
+    pub(crate) last_frame: HashMap<CompPair, TriggerExitRecGeneric<TriggerRxKind, TriggerTxKind>>,
+    /// The key of the record for every pair that has overlapped so far this frame
+    #[reflect(ignore)]
+    pub(crate) this_frame: HashMap<CompPair, CollKey>,
 }
 impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>
     TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>
@@ -85,6 +207,29 @@ impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>
         let key = self.map.len() as CollKey;
         self.map.insert(key, rec);
     }
+    /// Inserts the record for a pair of comps, setting its phase based on whether the pair was
+    /// overlapping last frame. Only one record is made for each pair each frame, `duplicate_recs`
+    /// says whether later ones replace it.
+    pub(crate) fn insert_pair(
+        &mut self,
+        pair: CompPair,
+        mut rec: TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>,
+        duplicate_recs: DuplicateRecs,
+    ) {
+        rec.phase = if self.last_frame.contains_key(&pair) {
+            TriggerPhase::Stay
+        } else {
+            TriggerPhase::Enter
+        };
+        if let Some(key) = self.this_frame.get(&pair) {
+            if duplicate_recs == DuplicateRecs::KeepLast {
+                self.map.insert(*key, rec);
+            }
+            return;
+        }
+        self.this_frame.insert(pair, self.map.len() as CollKey);
+        self.insert(rec);
+    }
     pub fn get(
         &self,
         key: &CollKey,
@@ -99,6 +244,65 @@ impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>
     }
 }
 
+/// Records a pair of trigger comps that were overlapping last frame but aren't anymore (including
+/// because either entity was despawned)
+#[derive(Debug, Clone, Reflect)]
+pub struct TriggerExitRecGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    /// Entity of the control associated with the rx. May no longer exist.
+    pub rx_ctrl: Entity,
+    /// The kind of the rx
+    pub rx_kind: TriggerRxKind,
+    /// The marker of the hbox on the rx
+    pub rx_hbox: HBoxMarker,
+    /// Entity of the control associated with the tx. May no longer exist.
+    pub tx_ctrl: Entity,
+    /// The kind of the tx
+    pub tx_kind: TriggerTxKind,
+    /// The marker of the hbox on the tx
+    pub tx_hbox: HBoxMarker,
+}
+#[derive(Resource, Debug, Reflect)]
+pub struct TriggerExitsGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    pub(crate) list: Vec<TriggerExitRecGeneric<TriggerRxKind, TriggerTxKind>>,
+}
+impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>
+    TriggerExitsGeneric<TriggerRxKind, TriggerTxKind>
+{
+    pub fn all(&self) -> Vec<&TriggerExitRecGeneric<TriggerRxKind, TriggerTxKind>> {
+        self.list.iter().collect()
+    }
+    /// Every exit this frame where the given entity was the rx
+    pub fn get_by_rx(
+        &self,
+        rx_ctrl: Entity,
+    ) -> Vec<&TriggerExitRecGeneric<TriggerRxKind, TriggerTxKind>> {
+        self.list
+            .iter()
+            .filter(|rec| rec.rx_ctrl == rx_ctrl)
+            .collect()
+    }
+    /// Every exit this frame where the given entity was the tx
+    pub fn get_by_tx(
+        &self,
+        tx_ctrl: Entity,
+    ) -> Vec<&TriggerExitRecGeneric<TriggerRxKind, TriggerTxKind>> {
+        self.list
+            .iter()
+            .filter(|rec| rec.tx_ctrl == tx_ctrl)
+            .collect()
+    }
+}
+
+/// Sent as an event, and triggered on both the rx and tx entities, for every `StaticCollRec`
+#[derive(Event, Debug, Clone, Deref)]
+pub struct OnStaticColl(pub StaticCollRec);
+
+/// Sent as an event, and triggered on both the rx and tx entities, for every `TriggerCollRecGeneric`
+#[derive(Event, Debug, Clone, Deref)]
+pub struct OnTriggerCollGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
+    pub TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>,
+);
+
 /// Helpful trait to categorize collisions by marked hitboxes
 pub trait ByHBox<'a, Record> {
     fn by_rx_hbox(self) -> HashMap<HBoxMarker, Vec<&'a Record>>;
@@ -166,9 +370,70 @@ impl<'a, TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>
     }
 }
 
+/// Once everything has moved, finds every pair of trigger comps that stopped overlapping
+pub(crate) fn update_trigger_exits<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
+    mut trigger_colls: ResMut<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
+    mut trigger_exits: ResMut<TriggerExitsGeneric<TriggerRxKind, TriggerTxKind>>,
+) {
+    let this_frame = std::mem::take(&mut trigger_colls.this_frame)
+        .into_iter()
+        .filter_map(|(pair, key)| {
+            let rec = trigger_colls.map.get(&key)?;
+            let exit_rec = TriggerExitRecGeneric {
+                rx_ctrl: rec.rx_ctrl,
+                rx_kind: rec.rx_kind.clone(),
+                rx_hbox: rec.rx_hbox,
+                tx_ctrl: rec.tx_ctrl,
+                tx_kind: rec.tx_kind.clone(),
+                tx_hbox: rec.tx_hbox,
+            };
+            Some((pair, exit_rec))
+        })
+        .collect();
+    let last_frame = std::mem::replace(&mut trigger_colls.last_frame, this_frame);
+    let mut exited = last_frame
+        .into_iter()
+        .filter(|(pair, _)| !trigger_colls.last_frame.contains_key(pair))
+        .collect::<Vec<_>>();
+    exited.sort_by_key(|(pair, _)| *pair);
+    trigger_exits
+        .list
+        .extend(exited.into_iter().map(|(_, rec)| rec));
+}
+
+/// Once all the records for the frame exist, sends them out as events and observer triggers
+pub(crate) fn send_coll_events<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
+    mut commands: Commands,
+    static_colls: Res<StaticColls>,
+    trigger_colls: Res<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
+    mut static_writer: EventWriter<OnStaticColl>,
+    mut trigger_writer: EventWriter<OnTriggerCollGeneric<TriggerRxKind, TriggerTxKind>>,
+) {
+    // Go in key order so things always happen in the order the collisions did
+    let mut static_keys = static_colls.map.keys().collect::<Vec<_>>();
+    static_keys.sort();
+    for key in static_keys {
+        let rec = &static_colls.map[key];
+        let event = OnStaticColl(rec.clone());
+        commands.trigger_targets(event.clone(), [rec.rx_ctrl, rec.tx_ctrl]);
+        static_writer.send(event);
+    }
+    let mut trigger_keys = trigger_colls.map.keys().collect::<Vec<_>>();
+    trigger_keys.sort();
+    for key in trigger_keys {
+        let rec = &trigger_colls.map[key];
+        let event = OnTriggerCollGeneric(rec.clone());
+        commands.trigger_targets(event.clone(), [rec.rx_ctrl, rec.tx_ctrl]);
+        trigger_writer.send(event);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn reset_colls_every_frame<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
     mut static_colls: ResMut<StaticColls>,
+    mut static_crushes: ResMut<StaticCrushes>,
     mut trigger_colls: ResMut<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
+    mut trigger_exits: ResMut<TriggerExitsGeneric<TriggerRxKind, TriggerTxKind>>,
     mut srx_ctrls: Query<&mut StaticRx>,
     mut stx_ctrls: Query<&mut StaticTx>,
     mut trx_ctrls: Query<&mut TriggerRxGeneric<TriggerRxKind>>,
@@ -176,7 +441,13 @@ fn reset_colls_every_frame<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKin
 ) {
     // Eh at some point we may want to shrink memory used, but this probably fine
     static_colls.map.clear();
+    static_colls.observed.clear();
+    static_colls.rubbed.clear();
+    static_crushes.map.clear();
     trigger_colls.map.clear();
+    // Only non-empty if something was moved after the exits were found last frame
+    trigger_colls.this_frame.clear();
+    trigger_exits.list.clear();
     macro_rules! clear_coll_keys {
         ($thing:expr) => {
             for mut thing in &mut $thing {
@@ -185,6 +456,9 @@ fn reset_colls_every_frame<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKin
         };
     }
     clear_coll_keys!(srx_ctrls);
+    for mut srx_ctrl in &mut srx_ctrls {
+        srx_ctrl.crush_keys.clear();
+    }
     clear_coll_keys!(stx_ctrls);
     clear_coll_keys!(trx_ctrls);
     clear_coll_keys!(ttx_ctrls);
@@ -193,11 +467,72 @@ fn reset_colls_every_frame<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKin
 pub(super) fn register_colls<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
     app: &mut App,
 ) {
-    app.insert_resource(StaticColls { map: default() });
-    app.insert_resource(TriggerCollsGeneric::<TriggerRxKind, TriggerTxKind> { map: default() });
+    app.insert_resource(StaticColls {
+        map: default(),
+        observed: default(),
+        rubbed: default(),
+    });
+    app.insert_resource(StaticCrushes { map: default() });
+    app.insert_resource(TriggerCollsGeneric::<TriggerRxKind, TriggerTxKind> {
+        map: default(),
+        last_frame: default(),
+        this_frame: default(),
+    });
+    app.insert_resource(TriggerExitsGeneric::<TriggerRxKind, TriggerTxKind> { list: default() });
+    app.add_event::<OnStaticColl>();
+    app.add_event::<OnTriggerCollGeneric<TriggerRxKind, TriggerTxKind>>();
 
     app.add_systems(
         First,
         reset_colls_every_frame::<TriggerRxKind, TriggerTxKind>.in_set(PhysicsSet),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dyno::Dyno, hbox::HBox, test_utils::*};
+
+    #[test]
+    fn trigger_enter_stay_exit() {
+        let mut app = test_app();
+        let spikes = app
+            .world_mut()
+            .spawn((
+                Pos::new(30.0, 0.0),
+                TriggerTx::single(TestTriggerTxKind::Spikes, HBox::new(10, 10)),
+            ))
+            .id();
+        let rx = app
+            .world_mut()
+            .spawn((
+                Pos::new(0.0, 0.0),
+                Dyno::new(120.0, 0.0),
+                TriggerRx::single(TestTriggerRxKind::Player, HBox::new(10, 10)),
+            ))
+            .id();
+        // What happened each frame, `None` for an exit
+        let mut events = vec![];
+        for _ in 0..60 {
+            app.update();
+            let trx = app.world().get::<TriggerRx>(rx).unwrap();
+            for rec in app
+                .world()
+                .resource::<TriggerColls>()
+                .get_refs(&trx.coll_keys)
+            {
+                assert_eq!(rec.tx_ctrl, spikes);
+                events.push(Some(rec.phase));
+            }
+            for exit in app.world().resource::<TriggerExits>().get_by_rx(rx) {
+                assert_eq!(exit.tx_ctrl, spikes);
+                events.push(None);
+            }
+        }
+        // Passing through the 10 px wide spikes at 2 px a frame touches them during 10 frames
+        let mut expected = vec![Some(TriggerPhase::Enter)];
+        expected.extend([Some(TriggerPhase::Stay); 9]);
+        expected.push(None);
+        assert_eq!(events, expected);
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+/// Which axis interesting dynos move along first
+#[derive(Clone, Copy, Debug, Default, Reflect, PartialEq, Eq)]
+pub enum AxisOrder {
+    /// Move horizontally, then vertically. Stops bugs going up and down against walls (celeste does this)
+    #[default]
+    HorizontalFirst,
+    /// Move vertically, then horizontally
+    VerticalFirst,
+}
+
+/// The order in which the static txs an rx comp is overlapping get resolved
+#[derive(Clone, Copy, Debug, Default, Reflect, PartialEq, Eq)]
+pub enum CandidateOrder {
+    /// Resolve whatever we overlap most first. Avoids weird behavior when sliding along a straight edge
+    #[default]
+    LargestOverlapFirst,
+    /// Don't sort, just resolve in a deterministic (but arbitrary) order
+    Unsorted,
+}
+
+/// Which record is kept when an observing static rx comp or a trigger rx comp hits the same tx comp
+/// more than once in a frame (it usually does, once for every inch moved). Only one is ever kept.
+#[derive(Clone, Copy, Debug, Default, Reflect, PartialEq, Eq)]
+pub enum DuplicateRecs {
+    /// Keep the first contact (rx and tx positions from when they first touched)
+    #[default]
+    KeepFirst,
+    /// Keep the last contact (rx and tx positions from the last time they touched)
+    KeepLast,
+}
+
+/// Tunes the movement algorithm. Build `PhysicsPluginGeneric` with it, or change the resource at runtime.
+#[derive(Resource, Clone, Debug, Reflect)]
+pub struct PhysicsConfig {
+    /// How far things move before resolving collisions again.
+    /// Smaller is more precise but slower.
+    pub inch: f32,
+    /// Which axis interesting dynos move along first
+    pub axis_order: AxisOrder,
+    /// The order overlapping static txs are resolved in
+    pub candidate_order: CandidateOrder,
+    /// How big (in both dimensions) each cell of the broadphase grid is.
+    /// Should be roughly the size of a typical tx comp.
+    pub broadphase_cell_size: f32,
+    /// How far away something can be and still count as touching for `Contacts`
+    pub contact_skin: f32,
+    /// Which record is kept for observers and triggers touching the same thing many times in a frame
+    pub duplicate_recs: DuplicateRecs,
+}
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            inch: 1.0,
+            axis_order: default(),
+            candidate_order: default(),
+            broadphase_cell_size: 64.0,
+            contact_skin: 0.5,
+            duplicate_recs: default(),
+        }
+    }
+}
+impl PhysicsConfig {
+    /// Panics if `inch` isn't positive, nothing would ever finish moving
+    pub fn with_inch(mut self, inch: f32) -> Self {
+        assert!(
+            inch > 0.0,
+            "PhysicsConfig inch must be positive, got {inch}"
+        );
+        self.inch = inch;
+        self
+    }
+    pub fn with_axis_order(mut self, axis_order: AxisOrder) -> Self {
+        self.axis_order = axis_order;
+        self
+    }
+    pub fn with_candidate_order(mut self, candidate_order: CandidateOrder) -> Self {
+        self.candidate_order = candidate_order;
+        self
+    }
+    /// Panics if `broadphase_cell_size` isn't positive
+    pub fn with_broadphase_cell_size(mut self, broadphase_cell_size: f32) -> Self {
+        assert!(
+            broadphase_cell_size > 0.0,
+            "PhysicsConfig broadphase_cell_size must be positive, got {broadphase_cell_size}"
+        );
+        self.broadphase_cell_size = broadphase_cell_size;
+        self
+    }
+    pub fn with_contact_skin(mut self, contact_skin: f32) -> Self {
+        self.contact_skin = contact_skin;
+        self
+    }
+    pub fn with_duplicate_recs(mut self, duplicate_recs: DuplicateRecs) -> Self {
+        self.duplicate_recs = duplicate_recs;
+        self
+    }
+    /// Panics if any value would stop physics from working. The fields can be changed directly at
+    /// runtime, so this is checked every frame, not just by the builders.
+    pub(crate) fn validate(&self) {
+        assert!(
+            self.inch > 0.0,
+            "PhysicsConfig inch must be positive, got {}",
+            self.inch
+        );
+        assert!(
+            self.broadphase_cell_size > 0.0,
+            "PhysicsConfig broadphase_cell_size must be positive, got {}",
+            self.broadphase_cell_size
+        );
+    }
+}
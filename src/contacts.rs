@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+
+use crate::{
+    broadphase::Broadphase,
+    bullet_time::{BulletTimeClass, BulletTimeGeneric},
+    config::PhysicsConfig,
+    dyno::Dyno,
+    pos::Pos,
+    statics::{StaticRx, StaticRxKind, StaticTx, StaticTxKind},
+};
+
+/// Add this next to a `StaticRx` to get a summary of which sides its default comps are touching
+/// something solid on. Filled in by physics every frame after everything has moved.
+/// Uses `PhysicsConfig::contact_skin`, so a body resting on the ground stays grounded even though
+/// it's only exactly touching it.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+pub struct Contacts {
+    /// The entity we're standing on, if any
+    pub ground: Option<Entity>,
+    /// The entity directly to our left, if any
+    pub left_wall: Option<Entity>,
+    /// The entity directly to our right, if any
+    pub right_wall: Option<Entity>,
+    /// The entity directly above us, if any
+    pub ceiling: Option<Entity>,
+}
+impl Contacts {
+    pub fn on_ground(&self) -> bool {
+        self.ground.is_some()
+    }
+    pub fn on_left_wall(&self) -> bool {
+        self.left_wall.is_some()
+    }
+    pub fn on_right_wall(&self) -> bool {
+        self.right_wall.is_some()
+    }
+    pub fn on_ceiling(&self) -> bool {
+        self.ceiling.is_some()
+    }
+}
+
+/// Surfaces with normals at least this vertical count as ground or ceiling, anything else is a wall
+const MIN_FLOOR_NORMAL_Y: f32 = 0.5;
+
+/// Returns the entity (other than `my_eid`) that the default comps of the srx are touching on the
+/// side `dir` points to, if any. If more than one, it's the one we're deepest into.
+/// Ground that rose this frame is felt for that much further down, since riders of rising txs are
+/// carried up by their velocity and end up the rise above them until the next frame.
+#[allow(clippy::too_many_arguments)]
+fn get_touching(
+    my_eid: Entity,
+    my_pos: Pos,
+    my_srx: &StaticRx,
+    dir: Vec2,
+    skin: f32,
+    max_rise: f32,
+    get_rise: impl Fn(Entity) -> f32,
+    pos_q: &Query<&Pos>,
+    stx_q: &Query<&StaticTx>,
+    broadphase: &Broadphase,
+) -> Option<Entity> {
+    let is_ground = dir.y < 0.0;
+    let mut best: Option<(Entity, f32)> = None;
+    for rx_comp in &my_srx.comps {
+        if rx_comp.kind != StaticRxKind::Default {
+            continue;
+        }
+        let my_thbox = rx_comp.shape.translated(my_pos.x, my_pos.y);
+        let mut query_rect = my_thbox.translated(dir.x * skin, dir.y * skin).get_bounds();
+        if is_ground {
+            query_rect.min.y -= max_rise;
+        }
+        for key in broadphase.statics.query_rect(query_rect) {
+            if key.eid == my_eid {
+                continue;
+            }
+            let Ok(stx) = stx_q.get(key.eid) else {
+                continue;
+            };
+            let Some(comp) = stx.comps.get(key.ix) else {
+                continue;
+            };
+            if !rx_comp.layers.interacts_with(&comp.layers) {
+                continue;
+            }
+            let reach = if is_ground {
+                skin + get_rise(key.eid)
+            } else {
+                skin
+            };
+            let feeler = my_thbox.translated(dir.x * reach, dir.y * reach);
+            let tx_pos = pos_q.get(key.eid).expect("Missing pos on stx");
+            let tx_thbox = comp.shape.translated(tx_pos.x, tx_pos.y);
+            let stops_us = match comp.kind {
+                StaticTxKind::Solid => true,
+                // One-way platforms are only ever ground, and only if we're on top of them
+                StaticTxKind::PassUp => is_ground && tx_thbox.max_y() <= my_thbox.min_y() + skin,
+            };
+            if !stops_us {
+                continue;
+            }
+            let Some((push, normal)) = feeler.get_push_out_n_normal(&tx_thbox) else {
+                continue;
+            };
+            let on_this_side = if dir.y != 0.0 {
+                normal.y * dir.y <= -MIN_FLOOR_NORMAL_Y
+            } else {
+                normal.y.abs() < MIN_FLOOR_NORMAL_Y && normal.x * dir.x < 0.0
+            };
+            if !on_this_side {
+                continue;
+            }
+            let depth = push.length();
+            let is_better = best.is_none_or(|(best_eid, best_depth)| {
+                depth > best_depth || (depth == best_depth && key.eid < best_eid)
+            });
+            if is_better {
+                best = Some((key.eid, depth));
+            }
+        }
+    }
+    best.map(|(eid, _)| eid)
+}
+
+/// Fills in the contact summary of everything that wants one
+pub(crate) fn update_contacts<TimeClass: BulletTimeClass>(
+    bullet_time: Res<BulletTimeGeneric<TimeClass>>,
+    config: Res<PhysicsConfig>,
+    broadphase: Res<Broadphase>,
+    pos_q: Query<&Pos>,
+    stx_q: Query<&StaticTx>,
+    stx_dyno_q: Query<&Dyno, With<StaticTx>>,
+    mut contacts_q: Query<(Entity, &StaticRx, &mut Contacts)>,
+) {
+    let get_rise = |eid: Entity| {
+        stx_dyno_q
+            .get(eid)
+            .map(|dyno| dyno.vel.y.max(0.0) * bullet_time.delta_secs())
+            .unwrap_or(0.0)
+    };
+    let max_rise = stx_dyno_q
+        .iter()
+        .map(|dyno| dyno.vel.y.max(0.0) * bullet_time.delta_secs())
+        .fold(0.0, f32::max);
+    for (eid, srx, mut contacts) in &mut contacts_q {
+        let pos = *pos_q.get(eid).expect("Missing pos on srx");
+        let touching = |dir: Vec2| {
+            get_touching(
+                eid,
+                pos,
+                srx,
+                dir,
+                config.contact_skin,
+                max_rise,
+                get_rise,
+                &pos_q,
+                &stx_q,
+                &broadphase,
+            )
+        };
+        *contacts = Contacts {
+            ground: touching(Vec2::NEG_Y),
+            left_wall: touching(Vec2::NEG_X),
+            right_wall: touching(Vec2::X),
+            ceiling: touching(Vec2::Y),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hbox::HBox, test_utils::*, PhysicsSet};
+
+    #[test]
+    fn grounded_on_rising_platform() {
+        let mut app = test_app();
+        app.add_systems(Update, gravity.before(PhysicsSet));
+        let platform = app
+            .world_mut()
+            .spawn((
+                Pos::new(0.0, -4.0),
+                Dyno::new(0.0, 120.0),
+                StaticTx::single(StaticTxKind::Solid, HBox::new(40, 8)),
+            ))
+            .id();
+        let rider = app
+            .world_mut()
+            .spawn((
+                Pos::new(0.0, 5.0),
+                Dyno::default(),
+                StaticRx::single(StaticRxKind::Default, HBox::new(10, 10)),
+                Contacts::default(),
+            ))
+            .id();
+        for _ in 0..60 {
+            app.update();
+            let contacts = app.world().get::<Contacts>(rider).unwrap();
+            assert_eq!(contacts.ground, Some(platform));
+        }
+    }
+}
@@ -60,6 +60,15 @@ impl HBox {
     pub fn get_marker(&self) -> HBoxMarker {
         self.marker
     }
+    /// The corners of the hitbox, counter-clockwise starting at the bottom left
+    pub fn get_corners(&self) -> [Vec2; 4] {
+        [
+            Vec2::new(self.min_x(), self.min_y()),
+            Vec2::new(self.max_x(), self.min_y()),
+            Vec2::new(self.max_x(), self.max_y()),
+            Vec2::new(self.min_x(), self.max_y()),
+        ]
+    }
 }
 
 // I don't care that this is super verbose, and maybe inefficient. I want it to be correct.
@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+
+use crate::hbox::{HBox, HBoxMarker};
+
+/// A circular hitbox. Like `HBox`, the offset is the center (relative to the ctrl's pos).
+#[derive(Clone, Debug, Reflect)]
+pub struct HCircle {
+    offset: Vec2,
+    radius: f32,
+    marker: HBoxMarker,
+}
+impl HCircle {
+    pub fn new(radius: f32) -> Self {
+        Self {
+            offset: default(),
+            radius,
+            marker: default(),
+        }
+    }
+    pub fn with_offset(mut self, x: f32, y: f32) -> Self {
+        self.offset.x = x;
+        self.offset.y = y;
+        self
+    }
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+    pub fn with_marker(mut self, marker: HBoxMarker) -> Self {
+        self.marker = marker;
+        self
+    }
+
+    pub fn translated(&self, x: f32, y: f32) -> Self {
+        Self {
+            offset: self.offset + Vec2::new(x, y),
+            radius: self.radius,
+            marker: self.marker,
+        }
+    }
+    pub fn min_x(&self) -> f32 {
+        self.offset.x - self.radius
+    }
+    pub fn max_x(&self) -> f32 {
+        self.offset.x + self.radius
+    }
+    pub fn min_y(&self) -> f32 {
+        self.offset.y - self.radius
+    }
+    pub fn max_y(&self) -> f32 {
+        self.offset.y + self.radius
+    }
+    pub fn get_offset(&self) -> Vec2 {
+        self.offset
+    }
+    pub fn get_radius(&self) -> f32 {
+        self.radius
+    }
+    pub fn get_marker(&self) -> HBoxMarker {
+        self.marker
+    }
+}
+
+impl HCircle {
+    /// Returns if the two circles overlap. Just touching doesn't count.
+    pub fn overlaps_with(&self, rhs: &Self) -> bool {
+        self.offset.distance_squared(rhs.offset) < (self.radius + rhs.radius).powi(2)
+    }
+
+    /// Returns if the circle overlaps the hitbox. Just touching doesn't count.
+    pub fn overlaps_with_hbox(&self, rhs: &HBox) -> bool {
+        let closest = closest_point_on_hbox(rhs, self.offset);
+        self.offset.distance_squared(closest) < self.radius.powi(2)
+    }
+
+    /// If the two circles overlap, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out(&self, rhs: &Self) -> Option<Vec2> {
+        if !self.overlaps_with(rhs) {
+            return None;
+        }
+        let diff = self.offset - rhs.offset;
+        let dist = diff.length();
+        // Perfectly on top of each other, no good answer so just go up
+        let dir = diff.try_normalize().unwrap_or(Vec2::Y);
+        Some(dir * (self.radius + rhs.radius - dist))
+    }
+
+    /// If the circle overlaps the hitbox, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out_of_hbox(&self, rhs: &HBox) -> Option<Vec2> {
+        if !self.overlaps_with_hbox(rhs) {
+            return None;
+        }
+        let closest = closest_point_on_hbox(rhs, self.offset);
+        if closest != self.offset {
+            // Center is outside the box, push away from the closest point
+            let diff = self.offset - closest;
+            return Some(diff.normalize() * (self.radius - diff.length()));
+        }
+        // Center is inside the box, push out the nearest side
+        let needed_left_push = rhs.min_x() - self.offset.x - self.radius;
+        let needed_right_push = rhs.max_x() - self.offset.x + self.radius;
+        let needed_down_push = rhs.min_y() - self.offset.y - self.radius;
+        let needed_up_push = rhs.max_y() - self.offset.y + self.radius;
+        let needed_hor_push = if needed_left_push.abs() < needed_right_push.abs() {
+            needed_left_push
+        } else {
+            needed_right_push
+        };
+        let needed_ver_push = if needed_down_push.abs() < needed_up_push.abs() {
+            needed_down_push
+        } else {
+            needed_up_push
+        };
+        let push = if needed_hor_push.abs() < needed_ver_push.abs() {
+            Vec2::new(needed_hor_push, 0.0)
+        } else {
+            Vec2::new(0.0, needed_ver_push)
+        };
+        Some(push)
+    }
+}
+
+impl HCircle {
+    /// Casts a ray (`dir` must be normalized) at the circle. If it hits within `max_dist`, returns
+    /// how far along the ray the hit is and the normal where it hit.
+    /// A ray starting inside hits immediately, with a normal facing back along it.
+    pub fn raycast(&self, origin: Vec2, dir: Vec2, max_dist: f32) -> Option<(f32, Vec2)> {
+        let to_origin = origin - self.offset;
+        let c = to_origin.length_squared() - self.radius.powi(2);
+        if c <= 0.0 {
+            return Some((0.0, -dir));
+        }
+        let b = to_origin.dot(dir);
+        let discriminant = b * b - c;
+        if b > 0.0 || discriminant < 0.0 {
+            return None;
+        }
+        let t = -b - discriminant.sqrt();
+        (t <= max_dist).then(|| (t, (origin + dir * t - self.offset).normalize_or_zero()))
+    }
+}
+
+impl HBox {
+    /// If the hitbox overlaps the circle, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out_of_circle(&self, rhs: &HCircle) -> Option<Vec2> {
+        rhs.get_push_out_of_hbox(self).map(|push| -push)
+    }
+}
+
+/// The point in (or on the edge of) the hbox closest to `point`
+fn closest_point_on_hbox(hbox: &HBox, point: Vec2) -> Vec2 {
+    Vec2::new(
+        point.x.clamp(hbox.min_x(), hbox.max_x()),
+        point.y.clamp(hbox.min_y(), hbox.max_y()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raycast() {
+        let circle = HCircle::new(2.0).with_offset(10.0, 0.0);
+        let (dist, normal) = circle.raycast(Vec2::ZERO, Vec2::X, 20.0).unwrap();
+        assert!((dist - 8.0).abs() < 0.001);
+        assert!(normal.distance(Vec2::NEG_X) < 0.001);
+        // Too short, pointing away, and missing to the side
+        assert_eq!(circle.raycast(Vec2::ZERO, Vec2::X, 5.0), None);
+        assert_eq!(circle.raycast(Vec2::ZERO, Vec2::NEG_X, 20.0), None);
+        assert_eq!(circle.raycast(Vec2::new(0.0, 3.0), Vec2::X, 20.0), None);
+        // Starting inside hits right away
+        assert_eq!(
+            circle.raycast(Vec2::new(10.0, 1.0), Vec2::X, 20.0),
+            Some((0.0, Vec2::NEG_X))
+        );
+    }
+}
@@ -0,0 +1,461 @@
+use bevy::prelude::*;
+
+use crate::{
+    hbox::{HBox, HBoxMarker},
+    hcircle::HCircle,
+    hslope::HSlope,
+};
+
+/// A convex polygon hitbox. The corners are relative to the offset, which is relative to the
+/// ctrl's pos (just like the center of an `HBox`).
+#[derive(Clone, Debug, Reflect)]
+pub struct HPoly {
+    offset: Vec2,
+    corners: Vec<Vec2>,
+    marker: HBoxMarker,
+}
+impl HPoly {
+    /// Makes a polygon out of the given corners, which must make a convex shape.
+    /// They can be in either winding order.
+    pub fn new<I: IntoIterator<Item = Vec2>>(corners: I) -> Self {
+        let mut corners = corners.into_iter().collect::<Vec<_>>();
+        if get_signed_area(&corners) < 0.0 {
+            corners.reverse();
+        }
+        debug_assert!(is_convex(&corners), "HPoly corners must be convex");
+        Self {
+            offset: default(),
+            corners,
+            marker: default(),
+        }
+    }
+    /// A regular polygon with `num_sides` sides, with every corner `radius` away from the center
+    pub fn regular(num_sides: u32, radius: f32) -> Self {
+        Self::new((0..num_sides).map(|ix| {
+            let angle = std::f32::consts::TAU * ix as f32 / num_sides as f32;
+            Vec2::from_angle(angle) * radius
+        }))
+    }
+    pub fn with_offset(mut self, x: f32, y: f32) -> Self {
+        self.offset.x = x;
+        self.offset.y = y;
+        self
+    }
+    /// Rotates the corners (counter-clockwise, in radians) around the offset
+    pub fn with_rotation(mut self, angle: f32) -> Self {
+        let rot = Vec2::from_angle(angle);
+        for corner in self.corners.iter_mut() {
+            *corner = rot.rotate(*corner);
+        }
+        self
+    }
+    pub fn with_marker(mut self, marker: HBoxMarker) -> Self {
+        self.marker = marker;
+        self
+    }
+
+    pub fn translated(&self, x: f32, y: f32) -> Self {
+        Self {
+            offset: self.offset + Vec2::new(x, y),
+            corners: self.corners.clone(),
+            marker: self.marker,
+        }
+    }
+    pub fn min_x(&self) -> f32 {
+        self.offset.x
+            + self
+                .corners
+                .iter()
+                .map(|c| c.x)
+                .fold(f32::INFINITY, f32::min)
+    }
+    pub fn max_x(&self) -> f32 {
+        self.offset.x
+            + self
+                .corners
+                .iter()
+                .map(|c| c.x)
+                .fold(f32::NEG_INFINITY, f32::max)
+    }
+    pub fn min_y(&self) -> f32 {
+        self.offset.y
+            + self
+                .corners
+                .iter()
+                .map(|c| c.y)
+                .fold(f32::INFINITY, f32::min)
+    }
+    pub fn max_y(&self) -> f32 {
+        self.offset.y
+            + self
+                .corners
+                .iter()
+                .map(|c| c.y)
+                .fold(f32::NEG_INFINITY, f32::max)
+    }
+    pub fn get_offset(&self) -> Vec2 {
+        self.offset
+    }
+    pub fn get_marker(&self) -> HBoxMarker {
+        self.marker
+    }
+    /// The corners of the polygon (not relative to the offset), counter-clockwise
+    pub fn get_corners(&self) -> Vec<Vec2> {
+        self.corners
+            .iter()
+            .map(|corner| self.offset + *corner)
+            .collect()
+    }
+}
+
+impl HPoly {
+    /// Returns if the two polygons overlap. Just touching doesn't count.
+    pub fn overlaps_with(&self, rhs: &Self) -> bool {
+        self.get_push_out(rhs).is_some()
+    }
+
+    /// If the two polygons overlap, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out(&self, rhs: &Self) -> Option<Vec2> {
+        get_sat_push_out(&self.get_corners(), &rhs.get_corners())
+    }
+
+    /// If the polygon overlaps the hitbox, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out_of_hbox(&self, rhs: &HBox) -> Option<Vec2> {
+        get_sat_push_out(&self.get_corners(), &rhs.get_corners())
+    }
+
+    /// If the polygon overlaps the circle, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out_of_circle(&self, rhs: &HCircle) -> Option<Vec2> {
+        get_circle_push_out_of_poly(rhs, &self.get_corners()).map(|push| -push)
+    }
+
+    /// If the polygon overlaps the slope, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out_of_slope(&self, rhs: &HSlope) -> Option<Vec2> {
+        get_sat_push_out(&self.get_corners(), &rhs.get_corners())
+    }
+}
+
+impl HCircle {
+    /// If the circle overlaps the polygon, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out_of_poly(&self, rhs: &HPoly) -> Option<Vec2> {
+        get_circle_push_out_of_poly(self, &rhs.get_corners())
+    }
+}
+
+/// Twice the signed area of the polygon. Positive if the corners are counter-clockwise.
+fn get_signed_area(corners: &[Vec2]) -> f32 {
+    (0..corners.len())
+        .map(|ix| corners[ix].perp_dot(corners[(ix + 1) % corners.len()]))
+        .sum()
+}
+
+/// Returns if the (counter-clockwise) corners make a convex polygon
+fn is_convex(corners: &[Vec2]) -> bool {
+    (0..corners.len()).all(|ix| {
+        let a = corners[ix];
+        let b = corners[(ix + 1) % corners.len()];
+        let c = corners[(ix + 2) % corners.len()];
+        (b - a).perp_dot(c - b) >= 0.0
+    })
+}
+
+/// Separating axis test between two convex polygons (corners counter-clockwise).
+/// If they overlap, returns the smallest push that gets `me` out of `other`.
+pub(crate) fn get_sat_push_out(me: &[Vec2], other: &[Vec2]) -> Option<Vec2> {
+    let project = |corners: &[Vec2], axis: Vec2| {
+        corners
+            .iter()
+            .map(|corner| corner.dot(axis))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), proj| {
+                (min.min(proj), max.max(proj))
+            })
+    };
+    let mut best: Option<Vec2> = None;
+    for corners in [me, other] {
+        for ix in 0..corners.len() {
+            let edge = corners[(ix + 1) % corners.len()] - corners[ix];
+            let axis = Vec2::new(edge.y, -edge.x).normalize_or_zero();
+            if axis == Vec2::ZERO {
+                continue;
+            }
+            let (my_min, my_max) = project(me, axis);
+            let (o_min, o_max) = project(other, axis);
+            if my_max <= o_min || o_max <= my_min {
+                return None;
+            }
+            let needed_pos_push = o_max - my_min;
+            let needed_neg_push = my_max - o_min;
+            let push = if needed_pos_push < needed_neg_push {
+                axis * needed_pos_push
+            } else {
+                -axis * needed_neg_push
+            };
+            if best.is_none_or(|best| push.length_squared() < best.length_squared()) {
+                best = Some(push);
+            }
+        }
+    }
+    best
+}
+
+/// If the circle overlaps the convex polygon (corners counter-clockwise), return the vec that you
+/// need to move the circle to get it out
+pub(crate) fn get_circle_push_out_of_poly(me: &HCircle, corners: &[Vec2]) -> Option<Vec2> {
+    let center = me.get_offset();
+    let radius = me.get_radius();
+    // How far the center is outside of each edge, and that edge's normal
+    let mut deepest_edge: Option<(f32, Vec2)> = None;
+    let mut closest: Option<Vec2> = None;
+    for ix in 0..corners.len() {
+        let start = corners[ix];
+        let edge = corners[(ix + 1) % corners.len()] - start;
+        let normal = Vec2::new(edge.y, -edge.x).normalize_or_zero();
+        let dist = (center - start).dot(normal);
+        if deepest_edge.is_none_or(|(best, _)| dist > best) {
+            deepest_edge = Some((dist, normal));
+        }
+        let along =
+            ((center - start).dot(edge) / edge.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+        let point = start + edge * along;
+        if closest.is_none_or(|best| center.distance_squared(point) < center.distance_squared(best))
+        {
+            closest = Some(point);
+        }
+    }
+    let (edge_dist, edge_normal) = deepest_edge?;
+    if edge_dist <= 0.0 {
+        // Center is inside, push out the nearest edge
+        return Some(edge_normal * (radius - edge_dist));
+    }
+    let diff = center - closest?;
+    let dist = diff.length();
+    (dist < radius).then(|| diff / dist * (radius - dist))
+}
+
+/// Returns if the point is inside (or on the edge of) the convex polygon (corners counter-clockwise)
+pub(crate) fn convex_contains_point(corners: &[Vec2], point: Vec2) -> bool {
+    (0..corners.len()).all(|ix| {
+        let start = corners[ix];
+        let edge = corners[(ix + 1) % corners.len()] - start;
+        edge.perp_dot(point - start) >= 0.0
+    })
+}
+
+/// Straight line distance from the point to the convex polygon (corners counter-clockwise).
+/// Zero if inside.
+pub(crate) fn convex_distance_to_point(corners: &[Vec2], point: Vec2) -> f32 {
+    if convex_contains_point(corners, point) {
+        return 0.0;
+    }
+    (0..corners.len())
+        .map(|ix| {
+            let start = corners[ix];
+            let edge = corners[(ix + 1) % corners.len()] - start;
+            let along = ((point - start).dot(edge) / edge.length_squared().max(f32::EPSILON))
+                .clamp(0.0, 1.0);
+            point.distance(start + edge * along)
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Manhattan distance from the point to the convex polygon (corners counter-clockwise).
+/// Zero if inside.
+pub(crate) fn convex_manhattan_distance_to_point(corners: &[Vec2], point: Vec2) -> f32 {
+    if convex_contains_point(corners, point) {
+        return 0.0;
+    }
+    let manhattan = |other: Vec2| (other - point).abs().element_sum();
+    // Along each edge, the closest spot is a corner or where it crosses the point's row or column
+    (0..corners.len())
+        .flat_map(|ix| {
+            let start = corners[ix];
+            let end = corners[(ix + 1) % corners.len()];
+            let edge = end - start;
+            let mut dists = vec![manhattan(start), manhattan(end)];
+            for axis in [Vec2::X, Vec2::Y] {
+                let span = edge.dot(axis);
+                if span != 0.0 {
+                    let along = (point - start).dot(axis) / span;
+                    if (0.0..=1.0).contains(&along) {
+                        dists.push(manhattan(start + edge * along));
+                    }
+                }
+            }
+            dists
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Casts a ray (`dir` must be normalized) at the convex polygon (corners counter-clockwise).
+/// If it hits within `max_dist`, returns how far along the ray the hit is and the normal of the
+/// edge that was hit. A ray starting inside hits immediately, with a normal facing back along it.
+pub(crate) fn raycast_convex(
+    corners: &[Vec2],
+    origin: Vec2,
+    dir: Vec2,
+    max_dist: f32,
+) -> Option<(f32, Vec2)> {
+    let mut t_enter: f32 = 0.0;
+    let mut t_exit = max_dist;
+    let mut enter_normal = -dir;
+    for ix in 0..corners.len() {
+        let start = corners[ix];
+        let edge = corners[(ix + 1) % corners.len()] - start;
+        let normal = Vec2::new(edge.y, -edge.x).normalize_or_zero();
+        if normal == Vec2::ZERO {
+            continue;
+        }
+        let outside_by = (origin - start).dot(normal);
+        let speed = dir.dot(normal);
+        if speed == 0.0 {
+            if outside_by > 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = -outside_by / speed;
+        if speed < 0.0 {
+            if t > t_enter {
+                t_enter = t;
+                enter_normal = normal;
+            }
+        } else {
+            t_exit = t_exit.min(t);
+        }
+        if t_enter > t_exit {
+            return None;
+        }
+    }
+    Some((t_enter, enter_normal))
+}
+
+/// The corners (counter-clockwise) of the smallest convex polygon containing all the points
+pub(crate) fn get_convex_hull(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    // Andrew's monotone chain: lower half left to right, then upper half right to left
+    let mut hull: Vec<Vec2> = vec![];
+    for pass in 0..2 {
+        let start_len = hull.len();
+        let ordered: Box<dyn Iterator<Item = &Vec2>> = if pass == 0 {
+            Box::new(points.iter())
+        } else {
+            Box::new(points.iter().rev())
+        };
+        for point in ordered {
+            while hull.len() >= start_len + 2 {
+                let a = hull[hull.len() - 2];
+                let b = hull[hull.len() - 1];
+                if (b - a).perp_dot(*point - b) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(*point);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Corners (counter-clockwise) of the rect from `min` to `max`
+    fn rect(min: Vec2, max: Vec2) -> Vec<Vec2> {
+        vec![min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+    }
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!(a.distance(b) < 0.001, "{a} != {b}");
+    }
+
+    #[test]
+    fn sat_push_out() {
+        let me = rect(Vec2::ZERO, Vec2::splat(4.0));
+        // Overlapping by 1 horizontally and 4 vertically, so out the side
+        let push = get_sat_push_out(&me, &rect(Vec2::new(3.0, 0.0), Vec2::new(7.0, 4.0)));
+        assert_close(push.unwrap(), Vec2::new(-1.0, 0.0));
+        let push = get_sat_push_out(&me, &rect(Vec2::new(-2.0, 3.5), Vec2::new(6.0, 8.0)));
+        assert_close(push.unwrap(), Vec2::new(0.0, -0.5));
+        // Touching and separated don't need a push
+        let touching = rect(Vec2::new(4.0, 0.0), Vec2::new(8.0, 4.0));
+        assert_eq!(get_sat_push_out(&me, &touching), None);
+        let separated = rect(Vec2::new(5.0, 5.0), Vec2::new(8.0, 8.0));
+        assert_eq!(get_sat_push_out(&me, &separated), None);
+    }
+
+    #[test]
+    fn circle_push_out_of_poly() {
+        let corners = rect(Vec2::splat(-10.0), Vec2::splat(10.0));
+        // Center inside, out the nearest edge
+        let inside = HCircle::new(2.0).with_offset(9.0, 0.0);
+        assert_close(
+            get_circle_push_out_of_poly(&inside, &corners).unwrap(),
+            Vec2::new(3.0, 0.0),
+        );
+        // Center outside but still overlapping, away from the closest point
+        let outside = HCircle::new(2.0).with_offset(11.0, 0.0);
+        assert_close(
+            get_circle_push_out_of_poly(&outside, &corners).unwrap(),
+            Vec2::new(1.0, 0.0),
+        );
+        let corner = HCircle::new(2.0).with_offset(11.0, 11.0);
+        let push = get_circle_push_out_of_poly(&corner, &corners).unwrap();
+        assert_close(push, Vec2::splat(1.0).normalize() * (2.0 - 2f32.sqrt()));
+        let away = HCircle::new(2.0).with_offset(13.0, 0.0);
+        assert_eq!(get_circle_push_out_of_poly(&away, &corners), None);
+    }
+
+    #[test]
+    fn raycast() {
+        let corners = rect(Vec2::ZERO, Vec2::splat(10.0));
+        let (dist, normal) =
+            raycast_convex(&corners, Vec2::new(-5.0, 5.0), Vec2::X, 100.0).unwrap();
+        assert!((dist - 5.0).abs() < 0.001);
+        assert_close(normal, Vec2::NEG_X);
+        // Starting inside hits right away
+        let (dist, normal) = raycast_convex(&corners, Vec2::splat(5.0), Vec2::Y, 100.0).unwrap();
+        assert_eq!(dist, 0.0);
+        assert_close(normal, Vec2::NEG_Y);
+        // Parallel to an edge and outside it
+        assert_eq!(
+            raycast_convex(&corners, Vec2::new(-5.0, 20.0), Vec2::X, 100.0),
+            None
+        );
+        // Missing entirely, and stopping short
+        let diag = Vec2::new(1.0, 1.0).normalize();
+        assert_eq!(
+            raycast_convex(&corners, Vec2::new(-5.0, 20.0), diag, 100.0),
+            None
+        );
+        assert_eq!(
+            raycast_convex(&corners, Vec2::new(-5.0, 5.0), Vec2::X, 3.0),
+            None
+        );
+    }
+
+    #[test]
+    fn hull_of_overlapping_boxes() {
+        let mut points = rect(Vec2::ZERO, Vec2::splat(4.0));
+        points.extend(rect(Vec2::splat(2.0), Vec2::splat(6.0)));
+        let hull = get_convex_hull(points);
+        assert_eq!(
+            hull,
+            vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(4.0, 0.0),
+                Vec2::new(6.0, 2.0),
+                Vec2::new(6.0, 6.0),
+                Vec2::new(2.0, 6.0),
+                Vec2::new(0.0, 4.0),
+            ]
+        );
+        assert!(get_signed_area(&hull) > 0.0);
+        assert!(is_convex(&hull));
+    }
+}
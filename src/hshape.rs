@@ -0,0 +1,295 @@
+use bevy::prelude::*;
+
+use crate::{
+    hbox::{HBox, HBoxMarker},
+    hcircle::HCircle,
+    hpoly::{
+        convex_contains_point, convex_distance_to_point, convex_manhattan_distance_to_point,
+        get_circle_push_out_of_poly, get_sat_push_out, raycast_convex, HPoly,
+    },
+    hslope::HSlope,
+};
+
+/// Any shape that a static or trigger comp can have.
+/// Anything that takes a shape also takes the inner shapes directly (`HBox`, `HCircle`, `HSlope`, `HPoly`).
+#[derive(Clone, Debug, Reflect)]
+pub enum HShape {
+    Box(HBox),
+    Circle(HCircle),
+    Slope(HSlope),
+    Poly(HPoly),
+}
+impl From<HBox> for HShape {
+    fn from(value: HBox) -> Self {
+        Self::Box(value)
+    }
+}
+impl From<HCircle> for HShape {
+    fn from(value: HCircle) -> Self {
+        Self::Circle(value)
+    }
+}
+impl From<HSlope> for HShape {
+    fn from(value: HSlope) -> Self {
+        Self::Slope(value)
+    }
+}
+impl From<HPoly> for HShape {
+    fn from(value: HPoly) -> Self {
+        Self::Poly(value)
+    }
+}
+impl HShape {
+    pub fn translated(&self, x: f32, y: f32) -> Self {
+        match self {
+            Self::Box(hbox) => Self::Box(hbox.translated(x, y)),
+            Self::Circle(circle) => Self::Circle(circle.translated(x, y)),
+            Self::Slope(slope) => Self::Slope(slope.translated(x, y)),
+            Self::Poly(poly) => Self::Poly(poly.translated(x, y)),
+        }
+    }
+    pub fn get_offset(&self) -> Vec2 {
+        match self {
+            Self::Box(hbox) => hbox.get_offset(),
+            Self::Circle(circle) => circle.get_offset(),
+            Self::Slope(slope) => slope.get_offset(),
+            Self::Poly(poly) => poly.get_offset(),
+        }
+    }
+    pub fn get_marker(&self) -> HBoxMarker {
+        match self {
+            Self::Box(hbox) => hbox.get_marker(),
+            Self::Circle(circle) => circle.get_marker(),
+            Self::Slope(slope) => slope.get_marker(),
+            Self::Poly(poly) => poly.get_marker(),
+        }
+    }
+    /// The smallest axis-aligned rect that contains the whole shape
+    pub fn get_bounds(&self) -> Rect {
+        match self {
+            Self::Box(hbox) => Rect::new(hbox.min_x(), hbox.min_y(), hbox.max_x(), hbox.max_y()),
+            Self::Circle(circle) => Rect::new(
+                circle.min_x(),
+                circle.min_y(),
+                circle.max_x(),
+                circle.max_y(),
+            ),
+            Self::Slope(slope) => {
+                Rect::new(slope.min_x(), slope.min_y(), slope.max_x(), slope.max_y())
+            }
+            Self::Poly(poly) => Rect::new(poly.min_x(), poly.min_y(), poly.max_x(), poly.max_y()),
+        }
+    }
+    pub fn min_x(&self) -> f32 {
+        self.get_bounds().min.x
+    }
+    pub fn max_x(&self) -> f32 {
+        self.get_bounds().max.x
+    }
+    pub fn min_y(&self) -> f32 {
+        self.get_bounds().min.y
+    }
+    pub fn max_y(&self) -> f32 {
+        self.get_bounds().max.y
+    }
+}
+
+impl HShape {
+    /// Returns if the two shapes overlap. Just touching doesn't count.
+    pub fn overlaps_with(&self, rhs: &Self) -> bool {
+        match (self, rhs) {
+            (Self::Box(me), Self::Box(other)) => me.overlaps_with(other),
+            (Self::Box(me), Self::Circle(other)) => other.overlaps_with_hbox(me),
+            (Self::Circle(me), Self::Box(other)) => me.overlaps_with_hbox(other),
+            (Self::Circle(me), Self::Circle(other)) => me.overlaps_with(other),
+            (Self::Box(me), Self::Slope(other)) => other.overlaps_with_hbox(me),
+            (Self::Circle(me), Self::Slope(other)) => me.overlaps_with_slope(other),
+            (Self::Slope(me), Self::Box(other)) => me.overlaps_with_hbox(other),
+            (Self::Slope(me), Self::Circle(other)) => other.overlaps_with_slope(me),
+            (Self::Slope(me), Self::Slope(other)) => me.get_push_out(other).is_some(),
+            (Self::Poly(_), _) | (_, Self::Poly(_)) => self.get_push_out(rhs).is_some(),
+        }
+    }
+
+    /// If the two shapes overlap, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out(&self, rhs: &Self) -> Option<Vec2> {
+        self.get_push_out_n_normal(rhs).map(|(push, _)| push)
+    }
+
+    /// Like `get_push_out`, but also returns the normal of the surface of rhs we were pushed out of.
+    /// This is just the direction of the push, except for the sloped side of an `HSlope`.
+    pub(crate) fn get_push_out_n_normal(&self, rhs: &Self) -> Option<(Vec2, Vec2)> {
+        let push = match (self, rhs) {
+            (Self::Box(me), Self::Box(other)) => me.get_push_out(other),
+            (Self::Box(me), Self::Circle(other)) => me.get_push_out_of_circle(other),
+            (Self::Circle(me), Self::Box(other)) => me.get_push_out_of_hbox(other),
+            (Self::Circle(me), Self::Circle(other)) => me.get_push_out(other),
+            (Self::Box(me), Self::Slope(other)) => {
+                return me.get_push_out_of_slope_n_normal(other);
+            }
+            (Self::Circle(me), Self::Slope(other)) => {
+                return me.get_push_out_of_slope_n_normal(other);
+            }
+            (Self::Slope(me), Self::Box(other)) => me.get_push_out_of_hbox(other),
+            (Self::Slope(me), Self::Circle(other)) => {
+                get_circle_push_out_of_poly(other, &me.get_corners()).map(|push| -push)
+            }
+            (Self::Slope(me), Self::Slope(other)) => {
+                get_sat_push_out(&me.get_corners(), &other.get_corners())
+            }
+            (Self::Box(me), Self::Poly(other)) => other.get_push_out_of_hbox(me).map(|push| -push),
+            (Self::Circle(me), Self::Poly(other)) => me.get_push_out_of_poly(other),
+            (Self::Slope(me), Self::Poly(other)) => {
+                other.get_push_out_of_slope(me).map(|push| -push)
+            }
+            (Self::Poly(me), Self::Box(other)) => me.get_push_out_of_hbox(other),
+            (Self::Poly(me), Self::Circle(other)) => me.get_push_out_of_circle(other),
+            (Self::Poly(me), Self::Slope(other)) => me.get_push_out_of_slope(other),
+            (Self::Poly(me), Self::Poly(other)) => me.get_push_out(other),
+        };
+        push.map(|push| (push, push.normalize_or_zero()))
+    }
+
+    /// Returns if the point is inside the shape. Being on the edge counts.
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        match self {
+            Self::Box(hbox) => convex_contains_point(&hbox.get_corners(), point),
+            Self::Circle(circle) => {
+                circle.get_offset().distance_squared(point) <= circle.get_radius().powi(2)
+            }
+            Self::Slope(slope) => convex_contains_point(&slope.get_corners(), point),
+            Self::Poly(poly) => convex_contains_point(&poly.get_corners(), point),
+        }
+    }
+
+    /// Straight line distance from the point to the closest part of the shape. Zero if inside.
+    pub fn euclidean_distance_to_point(&self, point: Vec2) -> f32 {
+        match self {
+            Self::Box(hbox) => convex_distance_to_point(&hbox.get_corners(), point),
+            Self::Circle(circle) => {
+                (circle.get_offset().distance(point) - circle.get_radius()).max(0.0)
+            }
+            Self::Slope(slope) => convex_distance_to_point(&slope.get_corners(), point),
+            Self::Poly(poly) => convex_distance_to_point(&poly.get_corners(), point),
+        }
+    }
+
+    /// Manhattan distance from the point to the closest part of the shape. Zero if inside.
+    pub fn manhattan_distance_to_point(&self, point: Vec2) -> f32 {
+        match self {
+            Self::Box(hbox) => hbox.manhattan_distance_to_point(point),
+            Self::Circle(circle) => {
+                let diff = (point - circle.get_offset()).abs();
+                let radius = circle.get_radius();
+                if diff.length_squared() <= radius.powi(2) {
+                    return 0.0;
+                }
+                // The closest point is either straight across or straight up/down from the point,
+                // or where the circle's normal is diagonal
+                let mut dist = f32::INFINITY;
+                if diff.x <= radius {
+                    dist = dist.min(diff.y - (radius.powi(2) - diff.x.powi(2)).sqrt());
+                }
+                if diff.y <= radius {
+                    dist = dist.min(diff.x - (radius.powi(2) - diff.y.powi(2)).sqrt());
+                }
+                if diff.min_element() >= radius / std::f32::consts::SQRT_2 {
+                    dist = dist.min(diff.x + diff.y - radius * std::f32::consts::SQRT_2);
+                }
+                dist.max(0.0)
+            }
+            Self::Slope(slope) => convex_manhattan_distance_to_point(&slope.get_corners(), point),
+            Self::Poly(poly) => convex_manhattan_distance_to_point(&poly.get_corners(), point),
+        }
+    }
+
+    /// Casts a ray (`dir` must be normalized) at the shape. If it hits within `max_dist`, returns
+    /// how far along the ray the hit is and the normal of the surface it hit.
+    /// A ray starting inside hits immediately, with a normal facing back along it.
+    pub fn raycast(&self, origin: Vec2, dir: Vec2, max_dist: f32) -> Option<(f32, Vec2)> {
+        match self {
+            Self::Box(hbox) => raycast_convex(&hbox.get_corners(), origin, dir, max_dist),
+            Self::Circle(circle) => circle.raycast(origin, dir, max_dist),
+            Self::Slope(slope) => raycast_convex(&slope.get_corners(), origin, dir, max_dist),
+            Self::Poly(poly) => raycast_convex(&poly.get_corners(), origin, dir, max_dist),
+        }
+    }
+
+    /// Roughly how much the two shapes overlap. Only used to decide what to resolve first.
+    /// NOTE: Assumes they are overlapping
+    pub(crate) fn area_overlapping_assuming_overlap(&self, rhs: &Self) -> f32 {
+        match (self, rhs) {
+            (Self::Box(me), Self::Box(other)) => me.area_overlapping_assuming_overlap(other),
+            _ => self
+                .get_bounds()
+                .intersect(rhs.get_bounds())
+                .size()
+                .element_product(),
+        }
+    }
+
+    /// The rect covered by this shape at any point while moving by `sweep` to get to where it is now
+    pub(crate) fn get_swept_rect(&self, sweep: Vec2) -> Rect {
+        let rect = self.get_bounds();
+        rect.union(Rect::from_corners(rect.min - sweep, rect.max - sweep))
+    }
+
+    /// Returns if the two shapes overlap at any point while self moves by `sweep` to get to where it is now
+    /// NOTE: Exact for boxes swept along a single axis, conservative otherwise
+    pub(crate) fn swept_overlaps_with(&self, sweep: Vec2, rhs: &Self) -> bool {
+        let rects_overlap = rects_overlap(self.get_swept_rect(sweep), rhs.get_bounds());
+        if let (Self::Box(_), Self::Box(_)) = (self, rhs) {
+            return rects_overlap;
+        }
+        if !rects_overlap {
+            return false;
+        }
+        if self.overlaps_with(rhs) {
+            return true;
+        }
+        // Check enough points along the way that neither shape can be skipped over
+        let thinnest = self
+            .get_bounds()
+            .size()
+            .min_element()
+            .min(rhs.get_bounds().size().min_element());
+        let step = (thinnest / 2.0).max(MIN_SWEEP_STEP);
+        let num_steps = (sweep.length() / step).ceil() as u32;
+        (1..=num_steps).any(|ix| {
+            let back = sweep * ix as f32 / num_steps as f32;
+            self.translated(-back.x, -back.y).overlaps_with(rhs)
+        })
+    }
+
+    /// How far self can move along `dir` (which must be a unit vector along an axis) before touching rhs.
+    /// Returns zero if they already overlap, and none if moving along `dir` never makes them touch.
+    /// NOTE: Exact for boxes, otherwise may be an underestimate (never an overestimate)
+    pub(crate) fn get_gap_along(&self, dir: Vec2, rhs: &Self) -> Option<f32> {
+        let me = self.get_bounds();
+        let other = rhs.get_bounds();
+        let (my_min, my_max, o_min, o_max) = if dir.x != 0.0 {
+            if me.max.y <= other.min.y || other.max.y <= me.min.y {
+                return None;
+            }
+            (me.min.x, me.max.x, other.min.x, other.max.x)
+        } else {
+            if me.max.x <= other.min.x || other.max.x <= me.min.x {
+                return None;
+            }
+            (me.min.y, me.max.y, other.min.y, other.max.y)
+        };
+        if dir.x + dir.y > 0.0 {
+            (my_min < o_max).then(|| (o_min - my_max).max(0.0))
+        } else {
+            (o_min < my_max).then(|| (my_min - o_max).max(0.0))
+        }
+    }
+}
+
+/// The smallest distance between checks when sweeping non-box shapes
+const MIN_SWEEP_STEP: f32 = 0.5;
+
+/// Returns if the two rects overlap. Just touching doesn't count.
+pub(crate) fn rects_overlap(a: Rect, b: Rect) -> bool {
+    a.min.x < b.max.x && b.min.x < a.max.x && a.min.y < b.max.y && b.min.y < a.max.y
+}
@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+
+use crate::{
+    hbox::{HBox, HBoxMarker},
+    hcircle::HCircle,
+    hpoly::{get_circle_push_out_of_poly, get_sat_push_out},
+};
+
+/// Which way the sloped side of an `HSlope` faces
+#[derive(Clone, Copy, Debug, Reflect, PartialEq, Eq)]
+pub enum SlopeFacing {
+    /// Floor that gets higher going right
+    UpLeft,
+    /// Floor that gets higher going left
+    UpRight,
+    /// Ceiling that gets lower going right
+    DownLeft,
+    /// Ceiling that gets lower going left
+    DownRight,
+}
+
+/// A right triangle hitbox, for slopes. The offset is the center of the rect that the triangle is
+/// half of, and the right angle is in the corner opposite to the way the slope faces.
+/// Box rxs standing on a floor slope are only ever pushed straight up, so they don't slide down it.
+#[derive(Clone, Debug, Reflect)]
+pub struct HSlope {
+    offset: Vec2,
+    size: Vec2,
+    facing: SlopeFacing,
+    marker: HBoxMarker,
+}
+impl HSlope {
+    pub fn new(w: f32, h: f32, facing: SlopeFacing) -> Self {
+        Self {
+            offset: default(),
+            size: Vec2::new(w, h),
+            facing,
+            marker: default(),
+        }
+    }
+    /// A 45° slope, as tall as it is wide
+    pub fn deg45(w: f32, facing: SlopeFacing) -> Self {
+        Self::new(w, w, facing)
+    }
+    /// A 22.5° slope, `w * tan(22.5°)` tall
+    pub fn deg22_5(w: f32, facing: SlopeFacing) -> Self {
+        Self::new(w, w * 22.5_f32.to_radians().tan(), facing)
+    }
+    pub fn with_offset(mut self, x: f32, y: f32) -> Self {
+        self.offset.x = x;
+        self.offset.y = y;
+        self
+    }
+    pub fn with_marker(mut self, marker: HBoxMarker) -> Self {
+        self.marker = marker;
+        self
+    }
+
+    pub fn translated(&self, x: f32, y: f32) -> Self {
+        Self {
+            offset: self.offset + Vec2::new(x, y),
+            size: self.size,
+            facing: self.facing,
+            marker: self.marker,
+        }
+    }
+    pub fn min_x(&self) -> f32 {
+        self.offset.x - self.size.x / 2.0
+    }
+    pub fn max_x(&self) -> f32 {
+        self.offset.x + self.size.x / 2.0
+    }
+    pub fn min_y(&self) -> f32 {
+        self.offset.y - self.size.y / 2.0
+    }
+    pub fn max_y(&self) -> f32 {
+        self.offset.y + self.size.y / 2.0
+    }
+    pub fn get_offset(&self) -> Vec2 {
+        self.offset
+    }
+    pub fn get_size(&self) -> Vec2 {
+        self.size
+    }
+    pub fn get_facing(&self) -> SlopeFacing {
+        self.facing
+    }
+    pub fn get_marker(&self) -> HBoxMarker {
+        self.marker
+    }
+}
+
+impl HSlope {
+    /// The corners of the triangle, counter-clockwise
+    pub fn get_corners(&self) -> [Vec2; 3] {
+        let bl = Vec2::new(self.min_x(), self.min_y());
+        let br = Vec2::new(self.max_x(), self.min_y());
+        let tr = Vec2::new(self.max_x(), self.max_y());
+        let tl = Vec2::new(self.min_x(), self.max_y());
+        match self.facing {
+            SlopeFacing::UpLeft => [bl, br, tr],
+            SlopeFacing::UpRight => [bl, br, tl],
+            SlopeFacing::DownLeft => [br, tr, tl],
+            SlopeFacing::DownRight => [bl, tr, tl],
+        }
+    }
+
+    /// The outward normal of the sloped side
+    pub fn get_normal(&self) -> Vec2 {
+        let (w, h) = (self.size.x, self.size.y);
+        match self.facing {
+            SlopeFacing::UpLeft => Vec2::new(-h, w),
+            SlopeFacing::UpRight => Vec2::new(h, w),
+            SlopeFacing::DownLeft => Vec2::new(-h, -w),
+            SlopeFacing::DownRight => Vec2::new(h, -w),
+        }
+        .normalize_or_zero()
+    }
+
+    /// Whether this is a floor (something can stand on the sloped side)
+    pub fn is_floor(&self) -> bool {
+        matches!(self.facing, SlopeFacing::UpLeft | SlopeFacing::UpRight)
+    }
+
+    /// How far the sloped side drops for every unit moved horizontally
+    pub fn get_steepness(&self) -> f32 {
+        if self.size.x == 0.0 {
+            return 0.0;
+        }
+        self.size.y / self.size.x
+    }
+
+    /// The x direction (-1 or 1) that goes down the sloped side of a floor
+    pub fn get_downhill_dir(&self) -> f32 {
+        match self.facing {
+            SlopeFacing::UpLeft | SlopeFacing::DownLeft => -1.0,
+            SlopeFacing::UpRight | SlopeFacing::DownRight => 1.0,
+        }
+    }
+
+    /// Returns if the slope overlaps the hitbox. Just touching doesn't count.
+    pub fn overlaps_with_hbox(&self, rhs: &HBox) -> bool {
+        get_sat_push_out(&rhs.get_corners(), &self.get_corners()).is_some()
+    }
+
+    /// If the two slopes overlap, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out(&self, rhs: &Self) -> Option<Vec2> {
+        get_sat_push_out(&self.get_corners(), &rhs.get_corners())
+    }
+
+    /// If the slope overlaps the hitbox, return the vec that you need to move self to get it out of rhs
+    pub fn get_push_out_of_hbox(&self, rhs: &HBox) -> Option<Vec2> {
+        get_sat_push_out(&self.get_corners(), &rhs.get_corners())
+    }
+}
+
+impl HBox {
+    /// If the hitbox overlaps the slope, return the vec that you need to move self to get it out of rhs.
+    /// NOTE: Pushes out of the sloped side of floors are always straight up.
+    pub fn get_push_out_of_slope(&self, rhs: &HSlope) -> Option<Vec2> {
+        self.get_push_out_of_slope_n_normal(rhs)
+            .map(|(push, _)| push)
+    }
+
+    /// Like `get_push_out_of_slope`, but also returns the normal of the surface we were pushed out of
+    pub(crate) fn get_push_out_of_slope_n_normal(&self, rhs: &HSlope) -> Option<(Vec2, Vec2)> {
+        let push = get_sat_push_out(&self.get_corners(), &rhs.get_corners())?;
+        let normal = rhs.get_normal();
+        let off_sloped_side = push.x != 0.0 && push.y != 0.0;
+        if off_sloped_side && rhs.is_floor() {
+            // Go straight up by however much it takes to get out of the sloped side
+            return Some((Vec2::new(0.0, push.length() / normal.y), normal));
+        }
+        if off_sloped_side {
+            return Some((push, normal));
+        }
+        Some((push, push.normalize_or_zero()))
+    }
+}
+
+impl HCircle {
+    /// Returns if the circle overlaps the slope. Just touching doesn't count.
+    pub fn overlaps_with_slope(&self, rhs: &HSlope) -> bool {
+        self.get_push_out_of_slope(rhs).is_some()
+    }
+
+    /// If the circle overlaps the slope, return the vec that you need to move self to get it out of rhs
+    /// NOTE: Pushes out of the sloped side of floors are always straight up.
+    pub fn get_push_out_of_slope(&self, rhs: &HSlope) -> Option<Vec2> {
+        self.get_push_out_of_slope_n_normal(rhs)
+            .map(|(push, _)| push)
+    }
+
+    /// Like `get_push_out_of_slope`, but also returns the normal of the surface we were pushed out of
+    pub(crate) fn get_push_out_of_slope_n_normal(&self, rhs: &HSlope) -> Option<(Vec2, Vec2)> {
+        let push = get_circle_push_out_of_poly(self, &rhs.get_corners())?;
+        let normal = rhs.get_normal();
+        let off_sloped_side = push.normalize_or_zero().dot(normal) > 1.0 - 1e-4;
+        if off_sloped_side && rhs.is_floor() {
+            // Same as boxes, straight up so things resting on the slope don't slide down it
+            return Some((Vec2::new(0.0, push.length() / normal.y), normal));
+        }
+        Some((push, push.normalize_or_zero()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!(a.distance(b) < 0.001, "{a} != {b}");
+    }
+
+    #[test]
+    fn box_on_floor_slope_pushed_straight_up() {
+        // Sloped side runs from (-10, -10) to (10, 10)
+        let slope = HSlope::deg45(20.0, SlopeFacing::UpLeft);
+        let hbox = HBox::new(4, 4).with_offset(2.0, 0.0);
+        let (push, normal) = hbox.get_push_out_of_slope_n_normal(&slope).unwrap();
+        assert_close(push, Vec2::new(0.0, 6.0));
+        assert_close(normal, Vec2::new(-1.0, 1.0).normalize());
+    }
+
+    #[test]
+    fn circle_on_floor_slope_pushed_straight_up() {
+        // Sloped side runs from (-10, -10) to (10, 10)
+        let slope = HSlope::deg45(20.0, SlopeFacing::UpLeft);
+        let circle = HCircle::new(2.0).with_offset(2.0, 0.0);
+        let (push, normal) = circle.get_push_out_of_slope_n_normal(&slope).unwrap();
+        assert_close(push, Vec2::new(0.0, 2.0 + 2.0 * 2.0_f32.sqrt()));
+        assert_close(normal, Vec2::new(-1.0, 1.0).normalize());
+        // Ceilings still push straight out
+        let ceiling = HSlope::deg45(20.0, SlopeFacing::DownRight);
+        let circle = HCircle::new(2.0).with_offset(0.0, -2.0);
+        let push = circle.get_push_out_of_slope(&ceiling).unwrap();
+        assert_close(push.normalize(), ceiling.get_normal());
+    }
+}
@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+
+/// Which collision layers a comp is on (`layer`) and which layers it interacts with (`mask`), as bitsets.
+/// Two comps only interact if each one's mask includes at least one of the other's layers.
+/// By default comps are on the first layer and interact with everything.
+#[derive(Clone, Copy, Debug, Reflect, PartialEq, Eq, std::hash::Hash)]
+pub struct CollLayers {
+    pub layer: u32,
+    pub mask: u32,
+}
+impl Default for CollLayers {
+    fn default() -> Self {
+        Self {
+            layer: 1,
+            mask: u32::MAX,
+        }
+    }
+}
+impl CollLayers {
+    pub fn new(layer: u32, mask: u32) -> Self {
+        Self { layer, mask }
+    }
+    /// Returns if comps with these layers should interact at all
+    pub fn interacts_with(&self, rhs: &Self) -> bool {
+        self.mask & rhs.layer != 0 && rhs.mask & self.layer != 0
+    }
+}
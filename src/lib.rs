@@ -3,25 +3,58 @@ use bevy::prelude::*;
 pub mod prelude {
     pub use super::bullet_time::{BulletTimeClass, BulletTimeGeneric};
     pub use super::colls::{
-        ByHBox, StaticCollRec, StaticColls, TriggerCollRecGeneric, TriggerCollsGeneric,
+        ByHBox, OnStaticColl, OnTriggerCollGeneric, StaticCollRec, StaticColls, StaticCrushRec,
+        StaticCrushes, TriggerCollRecGeneric, TriggerCollsGeneric, TriggerExitRecGeneric,
+        TriggerExitsGeneric, TriggerPhase,
     };
-    pub use super::dyno::Dyno;
+    pub use super::config::{AxisOrder, CandidateOrder, DuplicateRecs, PhysicsConfig};
+    pub use super::contacts::Contacts;
+    pub use super::dyno::{Dyno, Swept};
     pub use super::hbox::{HBox, HBoxMarker};
+    pub use super::hcircle::HCircle;
+    pub use super::hpoly::HPoly;
+    pub use super::hshape::HShape;
+    pub use super::hslope::{HSlope, SlopeFacing};
+    pub use super::layers::CollLayers;
+    pub use super::material::PhysicsMaterial;
+    pub use super::mover::{MoverGeneric, SlideResultGeneric};
     pub use super::plugin::PhysicsPluginGeneric;
     pub use super::pos::{IPos, Pos};
-    pub use super::statics::{StaticRx, StaticRxKind, StaticTx, StaticTxKind};
-    pub use super::triggers::{TriggerKind, TriggerRxGeneric, TriggerTxGeneric};
+    pub use super::spatial_query::{
+        CompKindGeneric, DistanceMetric, NearbyHitGeneric, OverlapHitGeneric, QueryFilterGeneric,
+        RaycastHitGeneric, ShapeCastHit, SpatialQueryGeneric,
+    };
+    pub use super::statics::{
+        Pushable, StaticRx, StaticRxComp, StaticRxKind, StaticTx, StaticTxComp, StaticTxKind,
+    };
+    pub use super::triggers::{
+        TriggerKind, TriggerRulesGeneric, TriggerRxComp, TriggerRxGeneric, TriggerTxComp,
+        TriggerTxGeneric,
+    };
     pub use super::PhysicsSet;
 }
 
+mod broadphase;
 mod bullet_time;
 mod colls;
+mod config;
+mod contacts;
 mod dyno;
 mod hbox;
+mod hcircle;
+mod hpoly;
+mod hshape;
+mod hslope;
+mod layers;
 mod logic;
+mod material;
+mod mover;
 mod plugin;
 mod pos;
+mod spatial_query;
 mod statics;
+#[cfg(test)]
+mod test_utils;
 mod triggers;
 
 /// The set that contains all physics related systems
@@ -1,35 +1,66 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::{
+        component::Tick,
+        system::{SystemChangeTick, SystemParam},
+    },
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 
 use crate::{
-    colls::{StaticCollRec, StaticColls, TriggerCollRecGeneric, TriggerCollsGeneric},
-    dyno::Dyno,
-    hbox::HBox,
+    broadphase::{self, Broadphase, CompKey},
+    colls::{
+        self, CollKey, CompPair, StaticCollRec, StaticColls, StaticCrushRec, StaticCrushes,
+        TriggerCollRecGeneric, TriggerCollsGeneric, TriggerPhase,
+    },
+    config::{AxisOrder, CandidateOrder, PhysicsConfig},
+    contacts,
+    dyno::{Dyno, Swept},
+    hbox::HBoxMarker,
+    hshape::{rects_overlap, HShape},
+    layers::CollLayers,
+    material::PhysicsMaterial,
+    mover::SlideResultGeneric,
     pos::Pos,
     prelude::{
-        BulletTimeClass, BulletTimeGeneric, StaticRx, StaticRxKind, StaticTx, StaticTxKind,
-        TriggerKind, TriggerRxGeneric, TriggerTxGeneric,
+        BulletTimeClass, BulletTimeGeneric, Pushable, StaticRx, StaticRxComp, StaticRxKind,
+        StaticTx, StaticTxComp, StaticTxKind, TriggerKind, TriggerRulesGeneric, TriggerRxGeneric,
+        TriggerTxGeneric,
     },
     PhysicsSet,
 };
 
 /// A helpful function to make sure physics things exist as we expect them to
+#[allow(clippy::type_complexity)]
 fn invariants(
+    config: Res<PhysicsConfig>,
     dyno_without_pos: Query<Entity, (With<Dyno>, Without<Pos>)>,
-    static_rx_n_tx: Query<Entity, (With<StaticRx>, With<StaticTx>)>,
-    moving_static_tx_vert_only: Query<&Dyno, With<StaticTx>>,
+    pushable_without_statics: Query<
+        Entity,
+        (With<Pushable>, Or<(Without<StaticRx>, Without<StaticTx>)>),
+    >,
 ) {
+    config.validate();
     debug_assert!(dyno_without_pos.is_empty());
-    debug_assert!(static_rx_n_tx.is_empty());
-    for dyno in &moving_static_tx_vert_only {
-        debug_assert!(dyno.vel.x.abs() == 0.0);
-    }
+    debug_assert!(pushable_without_statics.is_empty());
 }
 
-/// Moves dynos that have no statics and no trigger receivers
-fn move_uninteresting_dynos<TriggerRxKind: TriggerKind, TimeClass: BulletTimeClass>(
+/// Moves dynos that have no statics and no trigger receivers.
+/// Trigger txs moved this way remember how far they jumped, so they trigger along the whole way.
+fn move_uninteresting_dynos<
+    TriggerRxKind: TriggerKind,
+    TriggerTxKind: TriggerKind,
+    TimeClass: BulletTimeClass,
+>(
     bullet_time: Res<BulletTimeGeneric<TimeClass>>,
+    mut broadphase: ResMut<Broadphase>,
     mut ents: Query<
-        (&Dyno, &mut Pos),
+        (
+            Entity,
+            &Dyno,
+            &mut Pos,
+            Has<TriggerTxGeneric<TriggerTxKind>>,
+        ),
         (
             Without<StaticRx>,
             Without<StaticTx>,
@@ -37,101 +68,615 @@ fn move_uninteresting_dynos<TriggerRxKind: TriggerKind, TimeClass: BulletTimeCla
         ),
     >,
 ) {
-    for (dyno, mut pos) in &mut ents {
-        *pos += dyno.vel * bullet_time.delta_secs();
+    broadphase.trigger_sweeps.clear();
+    for (eid, dyno, mut pos, is_ttx) in &mut ents {
+        let delta = dyno.vel * bullet_time.delta_secs();
+        *pos += delta;
+        if is_ttx && delta != Vec2::ZERO {
+            broadphase.trigger_sweeps.insert(eid, delta);
+        }
     }
 }
 
-/// Moves static txs
+/// How far above the top of a tx an rx can be and still count as riding it
+const RIDE_SKIN: f32 = 1.0;
+
+/// Returns the bounds covering every one of the shapes, translated to the given pos
+fn get_ctrl_bounds<'a>(shapes: impl Iterator<Item = &'a HShape>, pos: Pos) -> Rect {
+    shapes.fold(Rect::EMPTY, |bounds, shape| {
+        bounds.union(shape.translated(pos.x, pos.y).get_bounds())
+    })
+}
+
+/// Returns true if any default comp of the rx is standing on top of any comp of the tx it interacts with.
+/// `extra_skin` lets riders of vertically moving txs (which lag a bit behind) still count.
+fn is_riding(srx: &StaticRx, rx_pos: Pos, stx: &StaticTx, tx_pos: Pos, extra_skin: f32) -> bool {
+    srx.comps
+        .iter()
+        .filter(|rx_comp| rx_comp.kind == StaticRxKind::Default)
+        .any(|rx_comp| {
+            let rx_thbox = rx_comp.shape.translated(rx_pos.x, rx_pos.y);
+            stx.comps.iter().any(|tx_comp| {
+                if !rx_comp.layers.interacts_with(&tx_comp.layers) {
+                    return false;
+                }
+                let tx_thbox = tx_comp.shape.translated(tx_pos.x, tx_pos.y);
+                let overlaps_x =
+                    rx_thbox.min_x() < tx_thbox.max_x() && tx_thbox.min_x() < rx_thbox.max_x();
+                let gap = rx_thbox.min_y() - tx_thbox.max_y();
+                overlaps_x && -RIDE_SKIN <= gap && gap <= RIDE_SKIN + extra_skin
+            })
+        })
+}
+
+/// Returns how far the rx needs to move horizontally (in the direction of `dir`) to get out of
+/// every solid comp of the tx, along with the rx comp and tx comp that needed the biggest shove.
+/// None if it doesn't need to move.
+fn get_hor_shove<'a>(
+    srx: &'a StaticRx,
+    rx_pos: Pos,
+    stx: &'a StaticTx,
+    tx_pos: Pos,
+    dir: f32,
+) -> Option<(f32, &'a StaticRxComp, &'a StaticTxComp)> {
+    let mut result: Option<(f32, &StaticRxComp, &StaticTxComp)> = None;
+    for rx_comp in srx.comps.iter() {
+        if rx_comp.kind != StaticRxKind::Default {
+            continue;
+        }
+        let rx_thbox = rx_comp.shape.translated(rx_pos.x, rx_pos.y);
+        for tx_comp in stx.comps.iter() {
+            if tx_comp.kind != StaticTxKind::Solid
+                || !rx_comp.layers.interacts_with(&tx_comp.layers)
+            {
+                continue;
+            }
+            let tx_thbox = tx_comp.shape.translated(tx_pos.x, tx_pos.y);
+            if !rx_thbox.overlaps_with(&tx_thbox) {
+                continue;
+            }
+            let needed = if dir > 0.0 {
+                tx_thbox.max_x() - rx_thbox.min_x()
+            } else {
+                tx_thbox.min_x() - rx_thbox.max_x()
+            };
+            if needed != 0.0 && result.is_none_or(|(shove, _, _)| needed.abs() > shove.abs()) {
+                result = Some((needed, rx_comp, tx_comp));
+            }
+        }
+    }
+    result
+}
+
+/// Returns every solid comp (other than those on `my_eid`) on layers we interact with that the
+/// thbox overlaps, along with the push that would be needed to get out of it
+fn get_solid_overlaps<'a>(
+    my_eid: Entity,
+    my_thbox: &HShape,
+    my_layers: &CollLayers,
+    stxs: impl Iterator<Item = (Entity, Pos, &'a StaticTx)>,
+) -> Vec<(Entity, HBoxMarker, Vec2)> {
+    stxs.filter(|(eid, _, _)| *eid != my_eid)
+        .flat_map(|(eid, pos, stx)| {
+            stx.comps
+                .iter()
+                .filter(|comp| comp.kind == StaticTxKind::Solid)
+                .filter(|comp| my_layers.interacts_with(&comp.layers))
+                .filter_map(move |comp| {
+                    let thbox = comp.shape.translated(pos.x, pos.y);
+                    my_thbox
+                        .get_push_out(&thbox)
+                        .map(|push| (eid, comp.shape.get_marker(), push))
+                })
+        })
+        .collect()
+}
+
+/// Returns how far something that just moved `moved` horizontally needs to back up to get out of
+/// everything it now overlaps (given the push out of each). Never more than it moved.
+fn get_hor_pull_back(moved: f32, pushes: impl Iterator<Item = Vec2>) -> f32 {
+    pushes
+        .map(|push| {
+            if push.x * moved < 0.0 {
+                push.x.abs().min(moved.abs())
+            } else {
+                moved.abs()
+            }
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Moves static txs (dynamic solids, which also have a static rx, move with the interesting dynos).
+/// Horizontal movement is inched so riders are carried along and anything in the way is shoved.
+/// Vertical movement is transferred to riders through their velocity when resolving collisions.
+#[allow(clippy::too_many_arguments)]
 fn move_static_txs<TimeClass: BulletTimeClass>(
     bullet_time: Res<BulletTimeGeneric<TimeClass>>,
-    mut ents: Query<(&Dyno, &mut Pos), (Without<StaticRx>, With<StaticTx>)>,
+    config: Res<PhysicsConfig>,
+    broadphase: Res<Broadphase>,
+    mut pos_q: Query<&mut Pos>,
+    stx_q: Query<(Entity, &Dyno, &StaticTx), Without<StaticRx>>,
+    all_stx_q: Query<(Entity, &StaticTx)>,
+    srx_q: Query<(Entity, &StaticRx)>,
+    dyno_q: Query<&Dyno>,
+    mut static_colls: ResMut<StaticColls>,
+    mut static_crushes: ResMut<StaticCrushes>,
 ) {
-    for (dyno, mut pos) in &mut ents {
-        *pos += dyno.vel * bullet_time.delta_secs();
+    for (tx_eid, tx_dyno, stx) in &stx_q {
+        let delta = tx_dyno.vel * bullet_time.delta_secs();
+        let skin = RIDE_SKIN + delta.y.abs();
+        // Only rxs close to where the tx is going can ride it or get shoved, so find those once
+        let tx_pos = *pos_q.get(tx_eid).expect("Missing pos on stx");
+        let tx_bounds = get_ctrl_bounds(stx.comps.iter().map(|comp| &comp.shape), tx_pos);
+        let tx_reach = tx_bounds
+            .union(Rect::from_corners(
+                tx_bounds.min + delta,
+                tx_bounds.max + delta,
+            ))
+            .inflate(skin);
+        let srx_bounds = srx_q
+            .iter()
+            .map(|(rx_eid, srx)| {
+                let rx_pos = *pos_q.get(rx_eid).expect("Missing pos on srx");
+                let shapes = srx.comps.iter().map(|comp| &comp.shape);
+                (rx_eid, get_ctrl_bounds(shapes, rx_pos))
+            })
+            .collect::<Vec<_>>();
+        let get_srxs_near = |rect: Rect| {
+            srx_bounds
+                .iter()
+                .filter(move |(_, bounds)| rects_overlap(rect, *bounds))
+                .map(|(rx_eid, _)| *rx_eid)
+        };
+        let nearby = get_srxs_near(tx_reach).collect::<Vec<_>>();
+        // Figure out who's riding before moving anything
+        let mut riders = nearby
+            .iter()
+            .copied()
+            .filter(|rx_eid| {
+                let (_, srx) = srx_q.get(*rx_eid).expect("Missing srx");
+                let rx_pos = *pos_q.get(*rx_eid).expect("Missing pos on srx");
+                is_riding(srx, rx_pos, stx, tx_pos, delta.y.abs())
+            })
+            .collect::<Vec<_>>();
+        let mut is_rider = riders.iter().copied().collect::<HashSet<_>>();
+        // Anything riding a dynamic solid that's being carried (like a stack of crates) comes too
+        let mut rider_ix = 0;
+        while rider_ix < riders.len() {
+            if let Ok((rider_eid, rider_stx)) = all_stx_q.get(riders[rider_ix]) {
+                let rider_pos = *pos_q.get(rider_eid).expect("Missing pos on stx");
+                let shapes = rider_stx.comps.iter().map(|comp| &comp.shape);
+                let rider_bounds = get_ctrl_bounds(shapes, rider_pos).inflate(skin);
+                for rx_eid in get_srxs_near(rider_bounds) {
+                    let (_, srx) = srx_q.get(rx_eid).expect("Missing srx");
+                    let rx_pos = *pos_q.get(rx_eid).expect("Missing pos on srx");
+                    if !is_rider.contains(&rx_eid)
+                        && is_riding(srx, rx_pos, rider_stx, rider_pos, delta.y.abs())
+                    {
+                        riders.push(rx_eid);
+                        is_rider.insert(rx_eid);
+                    }
+                }
+            }
+            rider_ix += 1;
+        }
+        // Inch horizontally
+        let mut amt_moved_hor: f32 = 0.0;
+        while amt_moved_hor < delta.x.abs() {
+            let moving_this_step = config.inch.min(delta.x.abs() - amt_moved_hor);
+            amt_moved_hor += moving_this_step;
+            let step = delta.x.signum() * moving_this_step;
+            pos_q.get_mut(tx_eid).expect("Missing pos on stx").x += step;
+            for rider in &riders {
+                let (_, rider_srx) = srx_q.get(*rider).expect("Rider without srx");
+                let mut rider_pos = *pos_q.get(*rider).expect("Missing pos on srx");
+                rider_pos.x += step;
+                // Riders stop at walls instead of being carried through them, the tx just slides
+                // out from under them
+                let mut pull_back: f32 = 0.0;
+                for rx_comp in rider_srx.comps.iter() {
+                    if rx_comp.kind != StaticRxKind::Default {
+                        continue;
+                    }
+                    let rx_thbox = rx_comp.shape.translated(rider_pos.x, rider_pos.y);
+                    let stxs = broadphase
+                        .statics
+                        .query_eids(&rx_thbox)
+                        .into_iter()
+                        .filter(|eid| *eid != tx_eid && !is_rider.contains(eid))
+                        .filter_map(|eid| all_stx_q.get(eid).ok())
+                        .map(|(eid, stx)| (eid, *pos_q.get(eid).expect("Missing pos on stx"), stx));
+                    let overlaps = get_solid_overlaps(*rider, &rx_thbox, &rx_comp.layers, stxs);
+                    let pushes = overlaps.into_iter().map(|(_, _, push)| push);
+                    pull_back = pull_back.max(get_hor_pull_back(step, pushes));
+                }
+                rider_pos.x -= step.signum() * pull_back;
+                *pos_q.get_mut(*rider).expect("Missing pos on srx") = rider_pos;
+            }
+            let tx_pos = *pos_q.get(tx_eid).expect("Missing pos on stx");
+            for &rx_eid in &nearby {
+                if is_rider.contains(&rx_eid) {
+                    continue;
+                }
+                let (_, srx) = srx_q.get(rx_eid).expect("Missing srx");
+                let mut rx_pos = pos_q.get_mut(rx_eid).expect("Missing pos on srx");
+                let Some((shove, shoved_comp, pusher_comp)) =
+                    get_hor_shove(srx, *rx_pos, stx, tx_pos, step)
+                else {
+                    continue;
+                };
+                let pusher_hbox = pusher_comp.shape.get_marker();
+                let pre_shove_pos = *rx_pos;
+                rx_pos.x += shove;
+                let rx_pos = *rx_pos;
+                // Record the shove like any other collision, so gameplay can tell what hit it
+                let normal = Vec2::new(shove.signum(), 0.0);
+                let rx_vel = dyno_q.get(rx_eid).map(|dyno| dyno.vel).unwrap_or_default();
+                let tx_perp = tx_dyno.vel.dot(normal) * normal;
+                static_colls.insert(StaticCollRec {
+                    push: Vec2::new(shove, 0.0),
+                    normal,
+                    rx_pos: pre_shove_pos,
+                    rx_perp: rx_vel.dot(normal) * normal - tx_perp,
+                    rx_par: rx_vel - rx_vel.dot(normal) * normal,
+                    rx_ctrl: rx_eid,
+                    rx_kind: shoved_comp.kind,
+                    rx_hbox: shoved_comp.shape.get_marker(),
+                    tx_pos,
+                    tx_ctrl: tx_eid,
+                    tx_kind: pusher_comp.kind,
+                    tx_hbox: pusher_hbox,
+                    tx_material: pusher_comp.material,
+                    surface_vel: pusher_comp.surface_vel
+                        - pusher_comp.surface_vel.dot(normal) * normal,
+                });
+                // If we shoved it into something else solid, it's being crushed. It stops flush
+                // against whatever's crushing it instead of being shoved through.
+                let mut pull_back: f32 = 0.0;
+                for rx_comp in srx.comps.iter() {
+                    if rx_comp.kind != StaticRxKind::Default {
+                        continue;
+                    }
+                    let rx_thbox = rx_comp.shape.translated(rx_pos.x, rx_pos.y);
+                    let stxs = broadphase
+                        .statics
+                        .query_eids(&rx_thbox)
+                        .into_iter()
+                        .filter(|eid| *eid != tx_eid)
+                        .filter_map(|eid| all_stx_q.get(eid).ok())
+                        .map(|(eid, stx)| (eid, *pos_q.get(eid).expect("Missing pos on stx"), stx));
+                    for (blocker_ctrl, blocker_hbox, push) in
+                        get_solid_overlaps(rx_eid, &rx_thbox, &rx_comp.layers, stxs)
+                    {
+                        pull_back = pull_back.max(get_hor_pull_back(shove, std::iter::once(push)));
+                        static_crushes.insert(StaticCrushRec {
+                            rx_pos,
+                            rx_ctrl: rx_eid,
+                            rx_hbox: rx_comp.shape.get_marker(),
+                            pusher_ctrl: tx_eid,
+                            pusher_hbox,
+                            blocker_ctrl,
+                            blocker_hbox,
+                        });
+                    }
+                }
+                pos_q.get_mut(rx_eid).expect("Missing pos on srx").x -= shove.signum() * pull_back;
+            }
+        }
+        // Then just move vertically
+        pos_q.get_mut(tx_eid).expect("Missing pos on stx").y += delta.y;
+    }
+}
+
+/// How far (beyond the tx's own upward movement and a single inch) an rx can sink into a PassUp
+/// before we let it through. Means anything landing on it this step is caught.
+const PASS_UP_TOLERANCE: f32 = 0.1;
+
+/// Everything needed to move interesting things around and resolve their collisions
+#[derive(SystemParam)]
+pub(crate) struct MoveCtx<'w, 's, TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    config: Res<'w, PhysicsConfig>,
+    broadphase: ResMut<'w, Broadphase>,
+    /// When the broadphase was last refreshed with txs that were spawned or moved outside physics
+    refreshed_at: Local<'s, Option<Tick>>,
+    change_tick: SystemChangeTick,
+    pos_q: Query<'w, 's, &'static mut Pos>,
+    dyno_q: Query<'w, 's, &'static mut Dyno>,
+    srx_q: Query<'w, 's, (Entity, &'static mut StaticRx)>,
+    stx_q: Query<'w, 's, (Entity, &'static mut StaticTx)>,
+    trx_q: Query<'w, 's, (Entity, &'static mut TriggerRxGeneric<TriggerRxKind>)>,
+    ttx_q: Query<'w, 's, (Entity, &'static mut TriggerTxGeneric<TriggerTxKind>)>,
+    pushable_q: Query<'w, 's, (), With<Pushable>>,
+    swept_q: Query<'w, 's, (), With<Swept>>,
+    /// How fast moving surfaces are carrying each entity this frame
+    conveyed: Local<'s, HashMap<Entity, Vec2>>,
+    static_colls: ResMut<'w, StaticColls>,
+    static_crushes: ResMut<'w, StaticCrushes>,
+    trigger_colls: ResMut<'w, TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
+    trigger_rules: Res<'w, TriggerRulesGeneric<TriggerRxKind, TriggerTxKind>>,
+}
+impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>
+    MoveCtx<'_, '_, TriggerRxKind, TriggerTxKind>
+{
+    pub(crate) fn try_get_pos(&self, eid: Entity) -> Option<Pos> {
+        self.pos_q.get(eid).ok().copied()
+    }
+    pub(crate) fn try_get_vel(&self, eid: Entity) -> Option<Vec2> {
+        self.dyno_q.get(eid).ok().map(|dyno| dyno.vel)
+    }
+    fn get_pos(&self, eid: Entity) -> Pos {
+        self.try_get_pos(eid).expect("No pos on interesting ent")
+    }
+    fn get_vel(&self, eid: Entity) -> Vec2 {
+        self.try_get_vel(eid).unwrap_or_default()
+    }
+    fn set_pos_n_vel(&mut self, eid: Entity, pos: Pos, vel: Vec2) {
+        *self.pos_q.get_mut(eid).expect("No pos on interesting ent") = pos;
+        if let Ok(mut dyno) = self.dyno_q.get_mut(eid) {
+            dyno.vel = vel;
+        }
+    }
+    /// Puts every tx spawned or moved since this system last ran into the broadphase where it is
+    /// now. Only needed once per run, nothing else can move them while we have the queries.
+    fn refresh_broadphase(&mut self) {
+        let this_run = self.change_tick.this_run();
+        if *self.refreshed_at == Some(this_run) {
+            return;
+        }
+        *self.refreshed_at = Some(this_run);
+        for (eid, stx) in self.stx_q.iter_mut() {
+            let pos = self.pos_q.get_mut(eid).expect("Missing pos on stx");
+            if !pos.is_changed() && !stx.is_changed() {
+                continue;
+            }
+            for (ix, comp) in stx.comps.iter().enumerate() {
+                let thbox = comp.shape.translated(pos.x, pos.y);
+                self.broadphase.refresh_static(CompKey { eid, ix }, &thbox);
+            }
+        }
+        for (eid, ttx) in self.ttx_q.iter_mut() {
+            let pos = self.pos_q.get_mut(eid).expect("Missing pos on ttx");
+            if !pos.is_changed() && !ttx.is_changed() {
+                continue;
+            }
+            for (ix, comp) in ttx.comps.iter().enumerate() {
+                let thbox = comp.shape.translated(pos.x, pos.y);
+                self.broadphase.refresh_trigger(CompKey { eid, ix }, &thbox);
+            }
+        }
+    }
+    fn get_coll_key_marks(&self) -> CollKeyMarks {
+        CollKeyMarks {
+            static_coll: self.static_colls.map.len() as CollKey,
+            static_crush: self.static_crushes.map.len() as CollKey,
+            trigger_coll: self.trigger_colls.map.len() as CollKey,
+        }
+    }
+}
+
+/// The key the next record of each type will get. Anything recorded after taking the marks has a
+/// key at or past them.
+#[derive(Clone, Copy, Default)]
+struct CollKeyMarks {
+    static_coll: CollKey,
+    static_crush: CollKey,
+    trigger_coll: CollKey,
+}
+
+/// Remembers that a moving surface is carrying the entity. If it's touching more than one, the
+/// fastest wins.
+fn convey(conveyed: &mut HashMap<Entity, Vec2>, eid: Entity, surface_vel: Vec2) {
+    if surface_vel == Vec2::ZERO {
+        return;
+    }
+    let current = conveyed.entry(eid).or_default();
+    if surface_vel.length_squared() > current.length_squared() {
+        *current = surface_vel;
+    }
+}
+
+/// Returns how far the default comps of the srx can move along `dir` (a unit vector along an axis)
+/// before touching something that would stop them, looking no further than `max_dist`.
+#[allow(clippy::too_many_arguments)]
+fn get_sweep_gap(
+    my_eid: Entity,
+    my_pos: Pos,
+    my_srx: &StaticRx,
+    dir: Vec2,
+    max_dist: f32,
+    pos_q: &Query<&mut Pos>,
+    stx_q: &Query<(Entity, &mut StaticTx)>,
+    broadphase: &Broadphase,
+) -> f32 {
+    let mut gap = f32::INFINITY;
+    for my_srx_comp in &my_srx.comps {
+        if my_srx_comp.kind != StaticRxKind::Default {
+            continue;
+        }
+        let my_thbox = my_srx_comp.shape.translated(my_pos.x, my_pos.y);
+        let sweep = dir * max_dist;
+        let swept_rect = my_thbox.translated(sweep.x, sweep.y).get_swept_rect(sweep);
+        for key in broadphase.statics.query_rect(swept_rect) {
+            if key.eid == my_eid {
+                continue;
+            }
+            let Ok((eid, stx)) = stx_q.get(key.eid) else {
+                continue;
+            };
+            let Some(comp) = stx.comps.get(key.ix) else {
+                continue;
+            };
+            if !my_srx_comp.layers.interacts_with(&comp.layers) {
+                continue;
+            }
+            let pos = pos_q.get(eid).expect("Missing pos on stx");
+            let thbox = comp.shape.translated(pos.x, pos.y);
+            let stops_us = match comp.kind {
+                StaticTxKind::Solid => true,
+                StaticTxKind::PassUp => dir.y < 0.0 && thbox.max_y() <= my_thbox.min_y(),
+            };
+            if !stops_us {
+                continue;
+            }
+            if let Some(this_gap) = my_thbox.get_gap_along(dir, &thbox) {
+                gap = gap.min(this_gap);
+            }
+        }
     }
+    gap
 }
 
 /// Resolves collisions for a single entity.
 /// If it has statics, it resolves static collisions and may update pos and vel
 /// If it has triggers, it will trigger as needed (duh)
+/// `sweep` is how far the entity just moved to get here. Anything it passed over in that time is
+/// observed/triggered, not just what it overlaps now.
+/// Static txs on any entity in `ignore` are skipped (always includes `my_eid`).
 fn resolve_collisions<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
+    ctx: &mut MoveCtx<TriggerRxKind, TriggerTxKind>,
     my_eid: Entity,
     my_pos: &mut Pos,
     my_vel: &mut Vec2,
-    my_srx: Option<(Entity, &StaticRx)>,
-    my_trx: Option<(Entity, &TriggerRxGeneric<TriggerRxKind>)>,
-    pos_q: &Query<&mut Pos>,
-    dyno_q: &Query<&mut Dyno>,
-    stx_q: &Query<(Entity, &mut StaticTx)>,
-    ttx_q: &Query<(Entity, &mut TriggerTxGeneric<TriggerTxKind>)>,
-    static_colls: &mut ResMut<StaticColls>,
-    trigger_colls: &mut ResMut<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
+    sweep: Vec2,
+    bullet_time_delta: f32,
+    ignore: &[Entity],
 ) {
     // Handle static collisions
     struct StaticCollCandidate {
         eid: Entity,
+        ix: usize,
         pos: Pos,
         kind: StaticTxKind,
-        thbox: HBox,
+        thbox: HShape,
+        material: PhysicsMaterial,
+        surface_vel: Vec2,
     }
 
     // Update all pos/dyno for static collisions, create records
-    if let Some((_, my_srx)) = my_srx {
-        for my_srx_comp in &my_srx.comps {
-            let mut my_thbox = my_srx_comp.hbox.translated(my_pos.x, my_pos.y);
-            // TODO: Performance engineer if needed
+    if let Ok((_, my_srx)) = ctx.srx_q.get(my_eid) {
+        for (my_ix, my_srx_comp) in my_srx.comps.iter().enumerate() {
+            let mut my_thbox = my_srx_comp.shape.translated(my_pos.x, my_pos.y);
             // In order to avoid weird behavior when sliding along a straight edge, do this
             // First filter to only things it's colliding with
-            let mut candidates = stx_q
-                .iter()
-                .flat_map(|(eid, stx)| {
-                    let pos = pos_q.get(eid).expect("Missing pos on stx");
-                    stx.comps.iter().map(move |comp| StaticCollCandidate {
+            let mut candidates = ctx
+                .broadphase
+                .statics
+                .query_rect(my_thbox.get_swept_rect(sweep))
+                .into_iter()
+                .filter(|key| key.eid != my_eid && !ignore.contains(&key.eid))
+                .filter_map(|key| {
+                    let (eid, stx) = ctx.stx_q.get(key.eid).ok()?;
+                    let comp = stx.comps.get(key.ix)?;
+                    // Comps on layers we don't interact with are never pushed out of or recorded
+                    if !my_srx_comp.layers.interacts_with(&comp.layers) {
+                        return None;
+                    }
+                    let pos = ctx.pos_q.get(eid).expect("Missing pos on stx");
+                    Some(StaticCollCandidate {
                         eid,
-                        pos: pos.clone(),
+                        ix: key.ix,
+                        pos: *pos,
                         kind: comp.kind,
-                        thbox: comp.hbox.translated(pos.x, pos.y),
+                        thbox: comp.shape.translated(pos.x, pos.y),
+                        material: comp.material,
+                        surface_vel: comp.surface_vel,
                     })
                 })
-                .filter(|candidate| candidate.eid != my_eid)
-                .filter(|candidate| my_thbox.overlaps_with(&candidate.thbox))
+                .filter(|candidate| match my_srx_comp.kind {
+                    StaticRxKind::Default => my_thbox.overlaps_with(&candidate.thbox),
+                    StaticRxKind::Observe => my_thbox.swept_overlaps_with(sweep, &candidate.thbox),
+                })
                 .collect::<Vec<_>>();
-            candidates.sort_by(|a, b| {
-                //shutup rust
-                let dist_a = a.thbox.area_overlapping_assuming_overlap(&my_thbox);
-                let dist_b = b.thbox.area_overlapping_assuming_overlap(&my_thbox);
-                dist_b.total_cmp(&dist_a)
-            });
+            match ctx.config.candidate_order {
+                CandidateOrder::LargestOverlapFirst => candidates.sort_by(|a, b| {
+                    //shutup rust
+                    let dist_a = a.thbox.area_overlapping_assuming_overlap(&my_thbox);
+                    let dist_b = b.thbox.area_overlapping_assuming_overlap(&my_thbox);
+                    dist_b.total_cmp(&dist_a)
+                }),
+                CandidateOrder::Unsorted => (),
+            }
+            // Remember who pushed us where, so we can tell who's to blame if we end up crushed
+            let mut pushers: Vec<(Entity, HBoxMarker, Vec2)> = vec![];
             for candidate in candidates {
-                let Some(push) = my_thbox.get_push_out(&candidate.thbox) else {
+                let (push, surface_normal) = match my_thbox.get_push_out_n_normal(&candidate.thbox)
+                {
+                    Some(push_n_normal) => push_n_normal,
+                    // Observers may have only passed over this during the sweep, still record it
+                    None if my_srx_comp.kind == StaticRxKind::Observe => (Vec2::ZERO, Vec2::ZERO),
                     // Likely means that resolving an earlier collision pushed us out of this box, do nothing
-                    continue;
+                    None => continue,
+                };
+                let tx_dyno = ctx.dyno_q.get(candidate.eid).cloned().unwrap_or_default();
+                let (push, surface_normal) = match candidate.kind {
+                    StaticTxKind::Solid => (push, surface_normal),
+                    StaticTxKind::PassUp => {
+                        // PassUp can only ever push up, and only if we were above it before this step
+                        let up_push = candidate.thbox.max_y() - my_thbox.min_y();
+                        let tolerance = ctx.config.inch
+                            + PASS_UP_TOLERANCE
+                            + tx_dyno.vel.y.max(0.0) * bullet_time_delta;
+                        if up_push > tolerance {
+                            continue;
+                        }
+                        (Vec2::new(0.0, up_push), Vec2::Y)
+                    }
                 };
 
                 // COLLISION ACTUALLY HAPPENING
-                let tx_dyno = dyno_q.get(candidate.eid).cloned().unwrap_or_default();
-                let mut old_perp = my_vel.dot(push.normalize_or_zero()) * push.normalize_or_zero();
-                let old_par = *my_vel - old_perp;
-                if push.y.abs() > 0.0 {
-                    old_perp.y -= tx_dyno.vel.y;
-                }
+                let normal = push.normalize_or_zero();
+                let tx_perp = tx_dyno.vel.dot(normal) * normal;
+                let old_perp = my_vel.dot(normal) * normal - tx_perp;
+                let old_par = *my_vel - my_vel.dot(normal) * normal;
+                // Moving surfaces only carry things along the surface, never into or out of it
+                let surface_vel = if my_srx_comp.kind == StaticRxKind::Default {
+                    candidate.surface_vel
+                        - candidate.surface_vel.dot(surface_normal) * surface_normal
+                } else {
+                    Vec2::ZERO
+                };
 
                 let coll_rec = StaticCollRec {
                     push,
-                    rx_pos: my_pos.clone(),
+                    normal: surface_normal,
+                    rx_pos: *my_pos,
                     rx_perp: old_perp,
                     rx_par: old_par,
                     rx_ctrl: my_eid,
                     rx_kind: my_srx_comp.kind,
-                    rx_hbox: my_srx_comp.hbox.get_marker(),
+                    rx_hbox: my_srx_comp.shape.get_marker(),
                     tx_pos: candidate.pos,
                     tx_ctrl: candidate.eid,
                     tx_kind: candidate.kind,
                     tx_hbox: candidate.thbox.get_marker(),
+                    tx_material: candidate.material,
+                    surface_vel,
+                };
+                // What's left of our velocity along the surface, and what bounces back off it
+                let pair = CompPair {
+                    rx_ctrl: my_eid,
+                    rx_ix: my_ix,
+                    tx_ctrl: candidate.eid,
+                    tx_ix: candidate.ix,
+                };
+                // Friction only once per pair per frame, no matter how many steps we touch it for.
+                // Moves that aren't part of a physics step (no time passes) don't use it up.
+                let get_new_par = |static_colls: &mut StaticColls| {
+                    if bullet_time_delta > 0.0 && static_colls.rub(pair) {
+                        candidate
+                            .material
+                            .apply_friction(old_par, bullet_time_delta)
+                    } else {
+                        old_par
+                    }
+                };
+                let bounce = if old_perp.dot(push) < 0.0 {
+                    -old_perp * candidate.material.restitution
+                } else {
+                    Vec2::ZERO
                 };
 
-                let mut do_push = |grr: &mut HBox| {
+                let mut do_push = |grr: &mut HShape| {
                     *my_pos += push;
                     *grr = grr.translated(push.x, push.y);
                 };
@@ -139,99 +684,401 @@ fn resolve_collisions<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
                 match (my_srx_comp.kind, candidate.kind) {
                     (StaticRxKind::Default, StaticTxKind::Solid) => {
                         // Solid collision, no breaking
-                        static_colls.insert(coll_rec);
+                        ctx.static_colls.insert(coll_rec);
                         do_push(&mut my_thbox);
-                        *my_vel = old_par + Vec2::new(0.0, tx_dyno.vel.y);
+                        pushers.push((candidate.eid, candidate.thbox.get_marker(), push));
+                        convey(&mut ctx.conveyed, my_eid, surface_vel);
+                        *my_vel = get_new_par(&mut ctx.static_colls) + tx_perp + bounce;
                         if old_perp.dot(push) > 0.0 {
                             *my_vel += old_perp;
                         }
                     }
-                    // TODO: Do I want this?
-                    // (StaticRxKind::Default, StaticTxKind::PassUp) => {
-                    //     // Any kind of passup
-                    //     if push.y > 0.0
-                    //         && old_perp.y < 0.0
-                    //         && other_thbox.max_y() - 1.1 < my_thbox.min_y()
-                    //     {
-                    //         add_coll_rec();
-                    //         do_push(&mut my_thbox);
-                    //         *my_vel = old_par + Vec2::new(0.0, tx_dyno.vel.y);
-                    //     }
-                    // }
+                    (StaticRxKind::Default, StaticTxKind::PassUp) => {
+                        // Only stop things that are moving down onto the platform
+                        if old_perp.y < 0.0 {
+                            ctx.static_colls.insert(coll_rec);
+                            do_push(&mut my_thbox);
+                            pushers.push((candidate.eid, candidate.thbox.get_marker(), push));
+                            convey(&mut ctx.conveyed, my_eid, surface_vel);
+                            *my_vel = get_new_par(&mut ctx.static_colls) + tx_perp + bounce;
+                        }
+                    }
                     (StaticRxKind::Observe, _) => {
-                        static_colls.insert(coll_rec);
+                        ctx.static_colls
+                            .insert_observed(pair, coll_rec, ctx.config.duplicate_recs);
                     }
                 }
             }
+            // If we're still stuck in something solid, the thing that pushed us into it is crushing us
+            if my_srx_comp.kind == StaticRxKind::Default && !pushers.is_empty() {
+                let stxs = ctx
+                    .broadphase
+                    .statics
+                    .query_eids(&my_thbox)
+                    .into_iter()
+                    .filter(|eid| !ignore.contains(eid))
+                    .filter_map(|eid| ctx.stx_q.get(eid).ok())
+                    .map(|(eid, stx)| (eid, *ctx.pos_q.get(eid).expect("Missing pos on stx"), stx));
+                for (blocker_ctrl, blocker_hbox, push) in
+                    get_solid_overlaps(my_eid, &my_thbox, &my_srx_comp.layers, stxs)
+                {
+                    let Some(&(pusher_ctrl, pusher_hbox, pusher_push)) =
+                        pushers.iter().rev().find(|pusher| {
+                            (pusher.0, pusher.1) != (blocker_ctrl, blocker_hbox)
+                                && pusher.2.dot(push) < 0.0
+                        })
+                    else {
+                        continue;
+                    };
+                    // Whichever of the two is moving into us harder is the one doing the crushing
+                    let speed_into_me = |eid: Entity, push: Vec2| {
+                        ctx.dyno_q
+                            .get(eid)
+                            .map(|dyno| dyno.vel.dot(push.normalize_or_zero()))
+                            .unwrap_or(0.0)
+                    };
+                    let mut crush_rec = StaticCrushRec {
+                        rx_pos: *my_pos,
+                        rx_ctrl: my_eid,
+                        rx_hbox: my_srx_comp.shape.get_marker(),
+                        pusher_ctrl,
+                        pusher_hbox,
+                        blocker_ctrl,
+                        blocker_hbox,
+                    };
+                    if speed_into_me(blocker_ctrl, push) > speed_into_me(pusher_ctrl, pusher_push) {
+                        crush_rec.pusher_ctrl = blocker_ctrl;
+                        crush_rec.pusher_hbox = blocker_hbox;
+                        crush_rec.blocker_ctrl = pusher_ctrl;
+                        crush_rec.blocker_hbox = pusher_hbox;
+                    } else {
+                        // Stay with the pusher instead of being shoved into (and then through) the
+                        // thing we're being crushed against
+                        *my_pos += push;
+                        my_thbox = my_thbox.translated(push.x, push.y);
+                        crush_rec.rx_pos = *my_pos;
+                    }
+                    ctx.static_crushes.insert(crush_rec);
+                }
+            }
         }
     }
 
     // Handle trigger collisions
     struct TriggerCollCandidate<InnerTriggerTxKind> {
         eid: Entity,
+        ix: usize,
+        sweep: Vec2,
         pos: Pos,
         kind: InnerTriggerTxKind,
-        thbox: HBox,
+        thbox: HShape,
     }
 
     // Create trigger coll records
-    if let Some((_, my_trx)) = my_trx {
-        for my_trx_comp in &my_trx.comps {
-            let my_thbox = my_trx_comp.hbox.translated(my_pos.x, my_pos.y);
-            let candidates = ttx_q
-                .iter()
-                .flat_map(|(eid, ttx)| {
-                    let pos = pos_q.get(eid).expect("Missing pos on ttx");
-                    ttx.comps.iter().map(move |comp| TriggerCollCandidate {
+    if let Ok((_, my_trx)) = ctx.trx_q.get(my_eid) {
+        for (my_ix, my_trx_comp) in my_trx.comps.iter().enumerate() {
+            let my_thbox = my_trx_comp.shape.translated(my_pos.x, my_pos.y);
+            let candidates = ctx
+                .broadphase
+                .triggers
+                .query_rect(my_thbox.get_swept_rect(sweep))
+                .into_iter()
+                .filter(|key| key.eid != my_eid)
+                .filter_map(|key| {
+                    let (eid, ttx) = ctx.ttx_q.get(key.eid).ok()?;
+                    let comp = ttx.comps.get(key.ix)?;
+                    if !my_trx_comp.layers.interacts_with(&comp.layers)
+                        || !ctx.trigger_rules.interacts(&my_trx_comp.kind, &comp.kind)
+                    {
+                        return None;
+                    }
+                    let pos = ctx.pos_q.get(eid).expect("Missing pos on ttx");
+                    Some(TriggerCollCandidate {
                         eid,
-                        pos: pos.clone(),
+                        ix: key.ix,
+                        sweep: ctx.broadphase.get_trigger_sweep(eid),
+                        pos: *pos,
                         kind: comp.kind.clone(),
-                        thbox: comp.hbox.translated(pos.x, pos.y),
+                        thbox: comp.shape.translated(pos.x, pos.y),
                     })
                 })
-                .filter(|candidate| candidate.eid != my_eid)
-                .filter(|candidate| my_thbox.overlaps_with(&candidate.thbox));
+                .filter(|candidate| {
+                    // Either of us may have passed over the other on the way here
+                    my_thbox.swept_overlaps_with(sweep, &candidate.thbox)
+                        || (candidate.sweep != Vec2::ZERO
+                            && candidate
+                                .thbox
+                                .swept_overlaps_with(candidate.sweep, &my_thbox))
+                });
             for candidate in candidates {
+                let pair = CompPair {
+                    rx_ctrl: my_eid,
+                    rx_ix: my_ix,
+                    tx_ctrl: candidate.eid,
+                    tx_ix: candidate.ix,
+                };
                 let coll_rec = TriggerCollRecGeneric {
-                    rx_pos: my_pos.clone(),
+                    // Filled in properly when inserted
+                    phase: TriggerPhase::Enter,
+                    rx_pos: *my_pos,
                     rx_ctrl: my_eid,
                     rx_kind: my_trx_comp.kind.clone(),
-                    rx_hbox: my_trx_comp.hbox.get_marker(),
+                    rx_hbox: my_trx_comp.shape.get_marker(),
                     tx_pos: candidate.pos,
                     tx_ctrl: candidate.eid,
                     tx_kind: candidate.kind,
                     tx_hbox: candidate.thbox.get_marker(),
                 };
-                trigger_colls.insert(coll_rec);
+                ctx.trigger_colls
+                    .insert_pair(pair, coll_rec, ctx.config.duplicate_recs);
+            }
+        }
+    }
+}
+
+/// Returns every static rx (not in `chain`) riding on top of the entity, if it's a static tx.
+/// Sorted so riders are always carried in the same order.
+fn get_riders<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
+    ctx: &MoveCtx<TriggerRxKind, TriggerTxKind>,
+    eid: Entity,
+    chain: &[Entity],
+) -> Vec<Entity> {
+    let Ok((_, stx)) = ctx.stx_q.get(eid) else {
+        return vec![];
+    };
+    let pos = ctx.get_pos(eid);
+    let mut riders = ctx
+        .srx_q
+        .iter()
+        .filter(|(rx_eid, _)| *rx_eid != eid && !chain.contains(rx_eid))
+        .filter(|(rx_eid, srx)| is_riding(srx, ctx.get_pos(*rx_eid), stx, pos, 0.0))
+        .map(|(rx_eid, _)| rx_eid)
+        .collect::<Vec<_>>();
+    riders.sort();
+    riders
+}
+
+/// How much further than the mover actually moved a pushable body can be overlapping it and still
+/// get shoved. Anything deeper was already overlapping before the move, which isn't our problem.
+const SHOVE_SLACK: f32 = 0.01;
+
+/// Returns each pushable body (not in `chain`) that the default comps of the entity overlap after
+/// moving `delta_x`, along with how far it needs to be shoved to get out of the way
+fn get_shoves<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
+    ctx: &MoveCtx<TriggerRxKind, TriggerTxKind>,
+    eid: Entity,
+    delta_x: f32,
+    chain: &[Entity],
+) -> Vec<(Entity, f32)> {
+    let Ok((_, srx)) = ctx.srx_q.get(eid) else {
+        return vec![];
+    };
+    let pos = ctx.get_pos(eid);
+    let mut shoves: Vec<(Entity, f32)> = vec![];
+    for rx_comp in &srx.comps {
+        if rx_comp.kind != StaticRxKind::Default {
+            continue;
+        }
+        let rx_thbox = rx_comp.shape.translated(pos.x, pos.y);
+        for key in ctx.broadphase.statics.query(&rx_thbox) {
+            if key.eid == eid || chain.contains(&key.eid) || !ctx.pushable_q.contains(key.eid) {
+                continue;
+            }
+            let Ok((_, stx)) = ctx.stx_q.get(key.eid) else {
+                continue;
+            };
+            let Some(comp) = stx.comps.get(key.ix) else {
+                continue;
+            };
+            if comp.kind != StaticTxKind::Solid || !rx_comp.layers.interacts_with(&comp.layers) {
+                continue;
+            }
+            let tx_pos = ctx.get_pos(key.eid);
+            let tx_thbox = comp.shape.translated(tx_pos.x, tx_pos.y);
+            if !rx_thbox.overlaps_with(&tx_thbox) {
+                continue;
+            }
+            let needed = if delta_x > 0.0 {
+                rx_thbox.max_x() - tx_thbox.min_x()
+            } else {
+                rx_thbox.min_x() - tx_thbox.max_x()
+            };
+            if needed * delta_x <= 0.0 || needed.abs() > delta_x.abs() + SHOVE_SLACK {
+                continue;
+            }
+            match shoves.iter_mut().find(|(other, _)| *other == key.eid) {
+                Some(shove) => {
+                    if needed.abs() > shove.1.abs() {
+                        shove.1 = needed;
+                    }
+                }
+                None => shoves.push((key.eid, needed)),
+            }
+        }
+    }
+    shoves
+}
+
+/// Moves an interesting entity by `delta` (along a single axis) and resolves its collisions.
+/// Moving horizontally shoves any pushable bodies in the way (which shove whatever is in their
+/// way, and so on), and anything riding a dynamic solid is carried along with it.
+/// `chain` is everything already being moved by this push. It's ignored when resolving so bodies
+/// in a chain don't fight each other. Returns how far the entity actually moved.
+fn move_body<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
+    ctx: &mut MoveCtx<TriggerRxKind, TriggerTxKind>,
+    eid: Entity,
+    delta: Vec2,
+    sweep: Vec2,
+    bullet_time_delta: f32,
+    chain: &mut Vec<Entity>,
+) -> Vec2 {
+    let start_pos = ctx.get_pos(eid);
+    chain.push(eid);
+    // Figure out who's riding before moving anything
+    let riders = if delta.x != 0.0 {
+        get_riders(ctx, eid, chain)
+    } else {
+        vec![]
+    };
+    let mut pos = start_pos + delta;
+    let mut vel = ctx.get_vel(eid);
+    ctx.set_pos_n_vel(eid, pos, vel);
+    if delta.x != 0.0 {
+        for (other, shove) in get_shoves(ctx, eid, delta.x, chain) {
+            move_body(
+                ctx,
+                other,
+                Vec2::new(shove, 0.0),
+                Vec2::ZERO,
+                bullet_time_delta,
+                chain,
+            );
+        }
+    }
+    resolve_collisions(
+        ctx,
+        eid,
+        &mut pos,
+        &mut vel,
+        sweep,
+        bullet_time_delta,
+        chain,
+    );
+    ctx.set_pos_n_vel(eid, pos, vel);
+    let moved = pos.as_vec2() - start_pos.as_vec2();
+    if moved.x != 0.0 {
+        for rider in riders {
+            move_body(
+                ctx,
+                rider,
+                Vec2::new(moved.x, 0.0),
+                Vec2::ZERO,
+                bullet_time_delta,
+                chain,
+            );
+        }
+    }
+    chain.pop();
+    moved
+}
+
+/// How far below an rx comp a floor slope can be for the rx to still be standing on it
+const SLOPE_SKIN: f32 = 0.5;
+
+/// If the entity is standing on a floor slope that goes down in the direction of `dir_x`, returns
+/// how far the slope drops for every unit moved horizontally
+fn get_slope_drop<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
+    ctx: &MoveCtx<TriggerRxKind, TriggerTxKind>,
+    eid: Entity,
+    dir_x: f32,
+) -> Option<f32> {
+    if dir_x == 0.0 || ctx.get_vel(eid).y > 0.0 {
+        return None;
+    }
+    let (_, srx) = ctx.srx_q.get(eid).ok()?;
+    let pos = ctx.get_pos(eid);
+    let mut drop: Option<f32> = None;
+    for rx_comp in &srx.comps {
+        if rx_comp.kind != StaticRxKind::Default {
+            continue;
+        }
+        let feeler = rx_comp.shape.translated(pos.x, pos.y - SLOPE_SKIN);
+        for key in ctx.broadphase.statics.query(&feeler) {
+            if key.eid == eid {
+                continue;
             }
+            let Ok((_, stx)) = ctx.stx_q.get(key.eid) else {
+                continue;
+            };
+            let Some(comp) = stx.comps.get(key.ix) else {
+                continue;
+            };
+            if comp.kind != StaticTxKind::Solid || !rx_comp.layers.interacts_with(&comp.layers) {
+                continue;
+            }
+            let tx_pos = ctx.get_pos(key.eid);
+            let slope_shape = comp.shape.translated(tx_pos.x, tx_pos.y);
+            let HShape::Slope(slope) = &slope_shape else {
+                continue;
+            };
+            if !slope.is_floor()
+                || slope.get_downhill_dir() != dir_x.signum()
+                || !feeler.overlaps_with(&slope_shape)
+            {
+                continue;
+            }
+            drop = Some(drop.unwrap_or(0.0).max(slope.get_steepness()));
         }
     }
+    drop
 }
 
 /// As we resolve collisions, we create the collisions records but don't put the corresponding
-/// keys in the needed vecs in the ctrls. This helper does that, assuming all colls have been resolved.
+/// keys in the needed vecs in the ctrls. This helper does that for every record made since `marks`,
+/// assuming all those colls have been resolved. Keys a ctrl already has (from moving something
+/// with a `MoverGeneric` earlier in the frame) aren't added twice.
 fn populate_ctrl_coll_keys<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
-    srx_q: &mut Query<(Entity, &mut StaticRx)>,
-    stx_q: &mut Query<(Entity, &mut StaticTx)>,
-    trx_q: &mut Query<(Entity, &mut TriggerRxGeneric<TriggerRxKind>)>,
-    ttx_q: &mut Query<(Entity, &mut TriggerTxGeneric<TriggerTxKind>)>,
-    static_colls: &ResMut<StaticColls>,
-    trigger_colls: &ResMut<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
+    ctx: &mut MoveCtx<TriggerRxKind, TriggerTxKind>,
+    marks: CollKeyMarks,
 ) {
-    for (key, coll) in &static_colls.map {
-        if let Ok((_, mut srx_ctrl)) = srx_q.get_mut(coll.rx_ctrl) {
-            srx_ctrl.coll_keys.push(*key);
+    for (key, crush) in &ctx.static_crushes.map {
+        if *key < marks.static_crush {
+            continue;
         }
-        if let Ok((_, mut stx_ctrl)) = stx_q.get_mut(coll.tx_ctrl) {
-            stx_ctrl.coll_keys.push(*key);
+        if let Ok((_, mut srx_ctrl)) = ctx.srx_q.get_mut(crush.rx_ctrl) {
+            if !srx_ctrl.crush_keys.contains(key) {
+                srx_ctrl.crush_keys.push(*key);
+            }
         }
     }
-    for (key, coll) in &trigger_colls.map {
-        if let Ok((_, mut trx_ctrl)) = trx_q.get_mut(coll.rx_ctrl) {
-            trx_ctrl.coll_keys.push(*key);
+    for (key, coll) in &ctx.static_colls.map {
+        if *key < marks.static_coll {
+            continue;
         }
-        if let Ok((_, mut ttx_ctrl)) = ttx_q.get_mut(coll.tx_ctrl) {
-            ttx_ctrl.coll_keys.push(*key);
+        if let Ok((_, mut srx_ctrl)) = ctx.srx_q.get_mut(coll.rx_ctrl) {
+            if !srx_ctrl.coll_keys.contains(key) {
+                srx_ctrl.coll_keys.push(*key);
+            }
+        }
+        if let Ok((_, mut stx_ctrl)) = ctx.stx_q.get_mut(coll.tx_ctrl) {
+            if !stx_ctrl.coll_keys.contains(key) {
+                stx_ctrl.coll_keys.push(*key);
+            }
+        }
+    }
+    for (key, coll) in &ctx.trigger_colls.map {
+        if *key < marks.trigger_coll {
+            continue;
+        }
+        if let Ok((_, mut trx_ctrl)) = ctx.trx_q.get_mut(coll.rx_ctrl) {
+            if !trx_ctrl.coll_keys.contains(key) {
+                trx_ctrl.coll_keys.push(*key);
+            }
+        }
+        if let Ok((_, mut ttx_ctrl)) = ctx.ttx_q.get_mut(coll.tx_ctrl) {
+            if !ttx_ctrl.coll_keys.contains(key) {
+                ttx_ctrl.coll_keys.push(*key);
+            }
         }
     }
 }
@@ -243,92 +1090,240 @@ fn move_interesting_dynos<
     TimeClass: BulletTimeClass,
 >(
     bullet_time: Res<BulletTimeGeneric<TimeClass>>,
-    mut pos_q: Query<&mut Pos>,
-    mut dyno_q: Query<&mut Dyno>,
-    mut srx_q: Query<(Entity, &mut StaticRx)>,
-    mut stx_q: Query<(Entity, &mut StaticTx)>,
-    mut trx_q: Query<(Entity, &mut TriggerRxGeneric<TriggerRxKind>)>,
-    mut ttx_q: Query<(Entity, &mut TriggerTxGeneric<TriggerTxKind>)>,
-    mut static_colls: ResMut<StaticColls>,
-    mut trigger_colls: ResMut<TriggerCollsGeneric<TriggerRxKind, TriggerTxKind>>,
-    // Objects that have a static rx. They may also have a trigger rx.
+    mut ctx: MoveCtx<TriggerRxKind, TriggerTxKind>,
+    // Objects that have a static rx (including dynamic solids), or a trigger rx and no static tx.
     // Basically all the stuff we should move in this system
     ents_q: Query<
         Entity,
         (
             With<Pos>,
-            Without<StaticTx>,
-            Or<(With<StaticRx>, With<TriggerRxGeneric<TriggerRxKind>>)>,
+            Or<(
+                With<StaticRx>,
+                (With<TriggerRxGeneric<TriggerRxKind>>, Without<StaticTx>),
+            )>,
+        ),
+    >,
+    // Solids that receive triggers. They're moved by `move_static_txs`, so only need triggering
+    solid_trx_q: Query<
+        Entity,
+        (
+            With<Pos>,
+            With<StaticTx>,
+            With<TriggerRxGeneric<TriggerRxKind>>,
+            Without<StaticRx>,
         ),
     >,
 ) {
+    let bullet_time_delta = bullet_time.delta_secs();
+    ctx.conveyed.clear();
+    // Move from the bottom up (then left to right) so stacks of bodies always move in the same order
+    let mut eids = ents_q
+        .iter()
+        .map(|eid| (eid, ctx.get_pos(eid)))
+        .collect::<Vec<_>>();
+    eids.sort_by(|(eid_a, pos_a), (eid_b, pos_b)| {
+        pos_a
+            .y
+            .total_cmp(&pos_b.y)
+            .then(pos_a.x.total_cmp(&pos_b.x))
+            .then(eid_a.cmp(eid_b))
+    });
     // First do the moving
-    for eid in &ents_q {
-        // Get the data
-        let mut scratch_pos = pos_q.get(eid).expect("No pos on interesting ent").clone();
-        let mut scratch_vel = dyno_q.get(eid).unwrap_or(&Dyno::default()).vel.clone();
-        let srx = srx_q.get(eid).ok();
-        let trx = trx_q.get(eid).ok();
-        debug_assert!(srx.is_some() || trx.is_some());
-        // Inch
-        macro_rules! call_resolve_collisions {
-            () => {{
-                resolve_collisions(
-                    eid,
-                    &mut scratch_pos,
-                    &mut scratch_vel,
-                    srx,
-                    trx,
-                    &pos_q,
-                    &dyno_q,
-                    &mut stx_q,
-                    &mut ttx_q,
-                    &mut static_colls,
-                    &mut trigger_colls,
-                )
-            }};
-        }
-        const DELTA_PER_INCH: f32 = 1.0;
+    for &(eid, _) in &eids {
+        let is_swept = ctx.swept_q.contains(eid);
+        debug_assert!(ctx.srx_q.contains(eid) || ctx.trx_q.contains(eid));
         // Resolve collisions once always so stationary objects are still pushed out of each other
-        call_resolve_collisions!();
-        // Inch horizontally
-        let mut amt_moved_hor: f32 = 0.0;
-        let max_inch_hor = scratch_vel.x.abs() * bullet_time.delta_secs();
-        while amt_moved_hor < max_inch_hor.min(scratch_vel.x.abs()) {
-            let dont_overshoot = (max_inch_hor.min(scratch_vel.x.abs()) - amt_moved_hor).max(0.0);
-            let moving_this_step = DELTA_PER_INCH.min(dont_overshoot);
-            amt_moved_hor += moving_this_step;
-            scratch_pos.x += scratch_vel.x.signum() * moving_this_step;
-            call_resolve_collisions!();
-        }
-        // Then inch vertically
-        let mut amt_moved_ver: f32 = 0.0;
-        let max_inch_ver = scratch_vel.y.abs() * bullet_time.delta_secs();
-        while amt_moved_ver < max_inch_ver.min(scratch_vel.y.abs()) {
-            let dont_overshoot = (max_inch_ver.min(scratch_vel.y.abs()) - amt_moved_ver).max(0.0);
-            let moving_this_step = DELTA_PER_INCH.min(dont_overshoot);
-            amt_moved_ver += moving_this_step;
-            scratch_pos.y += scratch_vel.y.signum() * moving_this_step;
-            call_resolve_collisions!();
-        }
+        let mut pos = ctx.get_pos(eid);
+        let mut vel = ctx.get_vel(eid);
+        resolve_collisions(
+            &mut ctx,
+            eid,
+            &mut pos,
+            &mut vel,
+            Vec2::ZERO,
+            bullet_time_delta,
+            &[],
+        );
+        ctx.set_pos_n_vel(eid, pos, vel);
         // NOTE: Why do this (inch horizontally then vertically)? Stops bugs going up and down against wall.
         // ^read: celeste does this
-        // Set the data
-        let mut set_pos = pos_q.get_mut(eid).expect("No pos on interesting ent");
-        *set_pos = scratch_pos;
-        if let Ok(mut set_dyno) = dyno_q.get_mut(eid) {
-            set_dyno.vel = scratch_vel;
-        }
-    }
-    // Then update the records in the controls once
-    populate_ctrl_coll_keys(
-        &mut srx_q,
-        &mut stx_q,
-        &mut trx_q,
-        &mut ttx_q,
-        &static_colls,
-        &trigger_colls,
-    );
+        let axes = match ctx.config.axis_order {
+            AxisOrder::HorizontalFirst => [Vec2::X, Vec2::Y],
+            AxisOrder::VerticalFirst => [Vec2::Y, Vec2::X],
+        };
+        for axis in axes {
+            let mut amt_moved: f32 = 0.0;
+            let max_inch = ctx.get_vel(eid).dot(axis).abs() * bullet_time_delta;
+            loop {
+                let vel = ctx.get_vel(eid);
+                let dont_overshoot = max_inch.min(vel.dot(axis).abs()) - amt_moved;
+                if dont_overshoot <= 0.0 {
+                    break;
+                }
+                let dir = axis * vel.dot(axis).signum();
+                // Normally move an inch, but swept dynos can skip straight to the inch that would
+                // put them into the first thing in their way (same place inching would stop)
+                let mut moving_this_step = ctx.config.inch.min(dont_overshoot);
+                if is_swept {
+                    moving_this_step = dont_overshoot;
+                    if let Ok((_, my_srx)) = ctx.srx_q.get(eid) {
+                        let gap = get_sweep_gap(
+                            eid,
+                            ctx.get_pos(eid),
+                            my_srx,
+                            dir,
+                            dont_overshoot,
+                            &ctx.pos_q,
+                            &ctx.stx_q,
+                            &ctx.broadphase,
+                        );
+                        let inches_to_hit = (gap / ctx.config.inch).floor() + 1.0;
+                        moving_this_step = (inches_to_hit * ctx.config.inch).min(dont_overshoot);
+                    }
+                }
+                amt_moved += moving_this_step;
+                let sweep = if is_swept {
+                    dir * moving_this_step
+                } else {
+                    Vec2::ZERO
+                };
+                let slope_drop = get_slope_drop(&ctx, eid, dir.x);
+                let moved = move_body(
+                    &mut ctx,
+                    eid,
+                    dir * moving_this_step,
+                    sweep,
+                    bullet_time_delta,
+                    &mut vec![],
+                );
+                // Stay stuck to slopes we're walking down instead of bouncing down them
+                if let Some(slope_drop) = slope_drop {
+                    move_body(
+                        &mut ctx,
+                        eid,
+                        Vec2::new(0.0, -moved.x.abs() * slope_drop),
+                        Vec2::ZERO,
+                        bullet_time_delta,
+                        &mut vec![],
+                    );
+                }
+            }
+        }
+    }
+    // Then let any moving surface each body touched carry it, an inch at a time like everything
+    // else. Surfaces touched while being carried don't carry it any further this frame.
+    let mut conveyed = std::mem::take(&mut *ctx.conveyed);
+    for (eid, _) in eids {
+        let Some(surface_vel) = conveyed.remove(&eid) else {
+            continue;
+        };
+        for axis in [Vec2::X, Vec2::Y] {
+            let total = surface_vel.dot(axis) * bullet_time_delta;
+            let mut amt_moved: f32 = 0.0;
+            while amt_moved < total.abs() {
+                let moving_this_step = ctx.config.inch.min(total.abs() - amt_moved);
+                amt_moved += moving_this_step;
+                move_body(
+                    &mut ctx,
+                    eid,
+                    axis * total.signum() * moving_this_step,
+                    Vec2::ZERO,
+                    bullet_time_delta,
+                    &mut vec![],
+                );
+            }
+        }
+    }
+    // Solids already moved this frame, trigger anything they passed over on the way
+    for eid in &solid_trx_q {
+        let mut pos = ctx.get_pos(eid);
+        let mut vel = ctx.get_vel(eid);
+        let sweep = vel * bullet_time_delta;
+        resolve_collisions(
+            &mut ctx,
+            eid,
+            &mut pos,
+            &mut vel,
+            sweep,
+            bullet_time_delta,
+            &[],
+        );
+    }
+    // Then update the records in the controls once. Crushes from moving the txs need keys too, so
+    // go over everything this frame.
+    populate_ctrl_coll_keys(&mut ctx, CollKeyMarks::default());
+}
+
+/// Moves an entity by exactly `delta` right now, an inch at a time along each axis like
+/// `move_interesting_dynos` does. No time passes, so friction isn't applied and nothing carries it.
+/// Returns how far it actually moved and the records made along the way.
+pub(crate) fn move_and_slide<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>(
+    ctx: &mut MoveCtx<TriggerRxKind, TriggerTxKind>,
+    eid: Entity,
+    delta: Vec2,
+) -> SlideResultGeneric<TriggerRxKind, TriggerTxKind> {
+    // This can run before physics has checked the config (or rebuilt the broadphase) this frame
+    ctx.config.validate();
+    ctx.refresh_broadphase();
+    let marks = ctx.get_coll_key_marks();
+    let start_pos = ctx.get_pos(eid);
+    let axes = match ctx.config.axis_order {
+        AxisOrder::HorizontalFirst => [Vec2::X, Vec2::Y],
+        AxisOrder::VerticalFirst => [Vec2::Y, Vec2::X],
+    };
+    for axis in axes {
+        let total = delta.dot(axis);
+        let dir = axis * total.signum();
+        let mut amt_moved: f32 = 0.0;
+        while amt_moved < total.abs() {
+            let moving_this_step = ctx.config.inch.min(total.abs() - amt_moved);
+            amt_moved += moving_this_step;
+            let slope_drop = get_slope_drop(ctx, eid, dir.x);
+            let moved = move_body(
+                ctx,
+                eid,
+                dir * moving_this_step,
+                Vec2::ZERO,
+                0.0,
+                &mut vec![],
+            );
+            if let Some(slope_drop) = slope_drop {
+                move_body(
+                    ctx,
+                    eid,
+                    Vec2::new(0.0, -moved.x.abs() * slope_drop),
+                    Vec2::ZERO,
+                    0.0,
+                    &mut vec![],
+                );
+            }
+            // Something's in the way, inching further would just push us back out again
+            if moved.dot(dir) <= 0.0 {
+                break;
+            }
+        }
+    }
+    ctx.conveyed.clear();
+    populate_ctrl_coll_keys(ctx, marks);
+    macro_rules! new_recs {
+        ($recs:expr, $mark:expr) => {{
+            let mut keys = $recs
+                .map
+                .keys()
+                .filter(|key| **key >= $mark)
+                .collect::<Vec<_>>();
+            keys.sort();
+            keys.into_iter()
+                .map(|key| $recs.map[key].clone())
+                .collect::<Vec<_>>()
+        }};
+    }
+    SlideResultGeneric {
+        moved: ctx.get_pos(eid).as_vec2() - start_pos.as_vec2(),
+        static_colls: new_recs!(ctx.static_colls, marks.static_coll),
+        static_crushes: new_recs!(ctx.static_crushes, marks.static_crush),
+        trigger_colls: new_recs!(ctx.trigger_colls, marks.trigger_coll),
+    }
 }
 
 pub(super) fn register_logic<
@@ -342,9 +1337,13 @@ pub(super) fn register_logic<
         Update,
         (
             invariants,
-            move_uninteresting_dynos::<TriggerRxKind, TimeClass>,
+            move_uninteresting_dynos::<TriggerRxKind, TriggerTxKind, TimeClass>,
+            broadphase::rebuild_broadphase::<TriggerRxKind, TriggerTxKind, TimeClass>,
             move_static_txs::<TimeClass>,
             move_interesting_dynos::<TriggerRxKind, TriggerTxKind, TimeClass>,
+            contacts::update_contacts::<TimeClass>,
+            colls::update_trigger_exits::<TriggerRxKind, TriggerTxKind>,
+            colls::send_coll_events::<TriggerRxKind, TriggerTxKind>,
         )
             .chain()
             .in_set(PhysicsSet)
@@ -352,3 +1351,230 @@ pub(super) fn register_logic<
             .before(super::PosSet),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hbox::HBox,
+        hcircle::HCircle,
+        hslope::{HSlope, SlopeFacing},
+        test_utils::*,
+    };
+
+    #[test]
+    fn crushed_between_closing_doors() {
+        let mut app = test_app();
+        let spawn_door = |app: &mut App, x: f32, vel_x: f32| {
+            app.world_mut()
+                .spawn((
+                    Pos::new(x, 0.0),
+                    Dyno::new(vel_x, 0.0),
+                    StaticTx::single(StaticTxKind::Solid, HBox::new(8, 40)),
+                ))
+                .id()
+        };
+        let left = spawn_door(&mut app, -20.0, 60.0);
+        let right = spawn_door(&mut app, 20.0, -60.0);
+        let rx = app
+            .world_mut()
+            .spawn((
+                Pos::new(0.0, 0.0),
+                StaticRx::single(StaticRxKind::Default, HBox::new(10, 10)),
+            ))
+            .id();
+        let mut crushed = false;
+        for _ in 0..30 {
+            app.update();
+            let srx = app.world().get::<StaticRx>(rx).unwrap();
+            for rec in app
+                .world()
+                .resource::<StaticCrushes>()
+                .get_refs(&srx.crush_keys)
+            {
+                assert!(
+                    (rec.pusher_ctrl, rec.blocker_ctrl) == (left, right)
+                        || (rec.pusher_ctrl, rec.blocker_ctrl) == (right, left)
+                );
+                crushed = true;
+            }
+        }
+        assert!(crushed);
+    }
+
+    #[test]
+    fn stacked_rider_stops_at_wall() {
+        let mut app = test_app();
+        app.world_mut().spawn((
+            Pos::new(0.0, 0.0),
+            Dyno::new(60.0, 0.0),
+            StaticTx::single(StaticTxKind::Solid, HBox::new(40, 8)),
+        ));
+        let crate_eid = app
+            .world_mut()
+            .spawn((
+                Pos::new(0.0, 9.0),
+                StaticRx::single(StaticRxKind::Default, HBox::new(10, 10)),
+                StaticTx::single(StaticTxKind::Solid, HBox::new(10, 10)),
+            ))
+            .id();
+        let rider = app
+            .world_mut()
+            .spawn((
+                Pos::new(0.0, 17.0),
+                StaticRx::single(StaticRxKind::Default, HBox::new(6, 6)),
+            ))
+            .id();
+        // Low enough to catch the rider but not the crate it's standing on
+        app.world_mut().spawn((
+            Pos::new(20.0, 20.0),
+            StaticTx::single(StaticTxKind::Solid, HBox::new(8, 8)),
+        ));
+        for _ in 0..30 {
+            app.update();
+        }
+        assert!((get_pos(&app, crate_eid).x - 30.0).abs() < 0.01);
+        assert!((get_pos(&app, rider).x - 13.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn crushed_rx_never_shoved_through_wall() {
+        let mut app = test_app();
+        app.world_mut().spawn((
+            Pos::new(0.0, 0.0),
+            Dyno::new(120.0, 0.0),
+            StaticTx::single(StaticTxKind::Solid, HBox::new(8, 40)),
+        ));
+        // Left side at x = 40
+        app.world_mut().spawn((
+            Pos::new(44.0, 0.0),
+            StaticTx::single(StaticTxKind::Solid, HBox::new(8, 40)),
+        ));
+        let rx = app
+            .world_mut()
+            .spawn((
+                Pos::new(20.0, 0.0),
+                Dyno::default(),
+                StaticRx::single(StaticRxKind::Default, HBox::new(10, 10)),
+            ))
+            .id();
+        let mut crushed = false;
+        for _ in 0..60 {
+            app.update();
+            assert!(get_pos(&app, rx).x <= 35.0 + 0.01);
+            let srx = app.world().get::<StaticRx>(rx).unwrap();
+            crushed |= !srx.crush_keys.is_empty();
+        }
+        assert!(crushed);
+    }
+
+    #[test]
+    fn circle_rests_on_floor_slope() {
+        let mut app = test_app();
+        app.add_systems(Update, gravity.before(PhysicsSet));
+        // Sloped side runs from (-20, -20) to (20, 20)
+        app.world_mut().spawn((
+            Pos::new(0.0, 0.0),
+            StaticTx::single(
+                StaticTxKind::Solid,
+                HSlope::deg45(40.0, SlopeFacing::UpLeft),
+            ),
+        ));
+        let circle = app
+            .world_mut()
+            .spawn((
+                Pos::new(0.0, 6.0),
+                Dyno::default(),
+                StaticRx::single(StaticRxKind::Default, HCircle::new(4.0)),
+            ))
+            .id();
+        for _ in 0..60 {
+            app.update();
+        }
+        let pos = get_pos(&app, circle);
+        assert!(pos.x.abs() < 0.01, "slid to {pos:?}");
+        assert!((pos.y - 4.0 * 2.0_f32.sqrt()).abs() < 1.0);
+    }
+
+    #[test]
+    fn uphill_friction_independent_of_speed() {
+        let friction = 6.0;
+        let get_kept = |speed: f32| {
+            let mut app = test_app();
+            // Sloped side runs from (-200, -200) to (200, 200), so moving right is uphill
+            app.world_mut().spawn((
+                Pos::new(0.0, 0.0),
+                StaticTx::single(
+                    StaticTxKind::Solid,
+                    HSlope::deg45(400.0, SlopeFacing::UpLeft),
+                )
+                .with_material(PhysicsMaterial::default().with_friction(friction)),
+            ));
+            let rx = app
+                .world_mut()
+                .spawn((
+                    Pos::new(0.0, 10.0),
+                    Dyno::new(speed, 0.0),
+                    StaticRx::single(StaticRxKind::Default, HBox::new(10, 10)),
+                ))
+                .id();
+            app.update();
+            app.world().get::<Dyno>(rx).unwrap().vel.x / speed
+        };
+        let expected = (-friction * DT).exp();
+        assert!((get_kept(60.0) - expected).abs() < 0.001);
+        assert!((get_kept(600.0) - expected).abs() < 0.001);
+    }
+
+    #[test]
+    fn conveyor_carries_once_per_frame() {
+        let mut app = test_app();
+        app.add_systems(Update, gravity.before(PhysicsSet));
+        app.world_mut().spawn((
+            Pos::new(0.0, -4.0),
+            StaticTx::single(StaticTxKind::Solid, HBox::new(400, 8))
+                .with_surface_vel(Vec2::new(60.0, 0.0)),
+        ));
+        let rx = app
+            .world_mut()
+            .spawn((
+                Pos::new(0.0, 5.0),
+                Dyno::default(),
+                StaticRx::single(StaticRxKind::Default, HBox::new(10, 10)),
+            ))
+            .id();
+        app.update();
+        for _ in 0..30 {
+            let last_x = get_pos(&app, rx).x;
+            app.update();
+            assert!((get_pos(&app, rx).x - last_x - 60.0 * DT).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn jump_up_through_pass_up_then_land_on_it() {
+        let mut app = test_app();
+        app.add_systems(Update, gravity.before(PhysicsSet));
+        // Top at y = 2
+        app.world_mut().spawn((
+            Pos::new(0.0, 0.0),
+            StaticTx::single(StaticTxKind::PassUp, HBox::new(40, 4)),
+        ));
+        let rx = app
+            .world_mut()
+            .spawn((
+                Pos::new(0.0, -20.0),
+                Dyno::new(0.0, 300.0),
+                StaticRx::single(StaticRxKind::Default, HBox::new(10, 10)),
+            ))
+            .id();
+        let mut highest: f32 = -20.0;
+        for _ in 0..120 {
+            app.update();
+            highest = highest.max(get_pos(&app, rx).y);
+        }
+        assert!(highest > 20.0);
+        assert!((get_pos(&app, rx).y - 7.0).abs() < 0.01);
+        assert_eq!(app.world().get::<Dyno>(rx).unwrap().vel.y, 0.0);
+    }
+}
@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+
+/// How a static tx comp's surface affects the velocity of rxs that hit it.
+/// The default keeps the classic behavior: velocity into the surface is zeroed and velocity along
+/// it is kept as is.
+#[derive(Clone, Copy, Debug, Reflect, PartialEq)]
+pub struct PhysicsMaterial {
+    /// How quickly velocity along the surface decays while in contact, as an exponential rate.
+    /// Each frame in contact scales it by `exp(-friction * delta_secs)`, so 0 keeps it all (ice),
+    /// 1 loses about 63% over a second of contact, and big numbers stop things almost instantly (mud).
+    pub friction: f32,
+    /// How much of the velocity into the surface is bounced back out. 0 stops dead, 1 is a
+    /// perfect bounce, more than 1 launches things (bouncy pads).
+    pub restitution: f32,
+    /// How much of its own acceleration an rx should get while touching this surface.
+    /// NOTE: Only gameplay data, the solver never reads it. Physics doesn't accelerate anything
+    /// itself, so gameplay code reads this off the `StaticCollRec` (e.g. low grip for ice).
+    pub grip: f32,
+}
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self {
+            friction: 0.0,
+            restitution: 0.0,
+            grip: 1.0,
+        }
+    }
+}
+impl PhysicsMaterial {
+    pub fn new(friction: f32, restitution: f32) -> Self {
+        Self {
+            friction,
+            restitution,
+            ..default()
+        }
+    }
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+    pub fn with_grip(mut self, grip: f32) -> Self {
+        self.grip = grip;
+        self
+    }
+
+    /// Applies friction to velocity along the surface, for contact lasting `delta_secs`.
+    /// NOTE: Should only be applied once per pair each frame
+    pub(crate) fn apply_friction(&self, par: Vec2, delta_secs: f32) -> Vec2 {
+        par * (-self.friction * delta_secs).exp()
+    }
+}
@@ -0,0 +1,107 @@
+//! Moving things by an exact amount right now, instead of setting `Dyno::vel` and waiting for
+//! physics to run. Handy for scripted motion like grapple pulls or knockback curves.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    colls::{StaticCollRec, StaticCrushRec, TriggerCollRecGeneric},
+    logic::{self, MoveCtx},
+    pos::Pos,
+    triggers::TriggerKind,
+};
+
+/// What happened when moving something with `MoverGeneric`
+#[derive(Clone, Debug, Reflect)]
+pub struct SlideResultGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    /// How far the entity actually moved. Less than asked for if something was in the way.
+    pub moved: Vec2,
+    /// Static collisions made by the move, in the order they happened
+    pub static_colls: Vec<StaticCollRec>,
+    /// Crushes made by the move, in the order they happened
+    pub static_crushes: Vec<StaticCrushRec>,
+    /// Trigger collisions made by the move, in the order they happened
+    pub trigger_colls: Vec<TriggerCollRecGeneric<TriggerRxKind, TriggerTxKind>>,
+}
+
+/// Moves entities against the world immediately, resolving collisions exactly like physics does.
+/// Records made this way are also added to the usual resources and ctrls. Run before `PhysicsSet`
+/// so they also go out as events (and count towards trigger exits) this frame. Moves made after
+/// `PhysicsSet` still return their records, but they're cleared before the next frame.
+/// NOTE: Needs mutable access to `Pos`, `Dyno`, the static and trigger ctrls, and the coll resources,
+/// so a system using it can't also query those (bevy will panic). Use `get_pos`/`get_vel` instead.
+#[derive(SystemParam)]
+pub struct MoverGeneric<'w, 's, TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    ctx: MoveCtx<'w, 's, TriggerRxKind, TriggerTxKind>,
+}
+impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>
+    MoverGeneric<'_, '_, TriggerRxKind, TriggerTxKind>
+{
+    /// Moves the entity by `delta` now, sliding along (and shoving, and carrying riders with) the
+    /// static world. Velocity is updated by anything it hits, the same as normal movement.
+    /// Panics if the entity has no `Pos`.
+    pub fn move_and_slide(
+        &mut self,
+        eid: Entity,
+        delta: Vec2,
+    ) -> SlideResultGeneric<TriggerRxKind, TriggerTxKind> {
+        logic::move_and_slide(&mut self.ctx, eid, delta)
+    }
+
+    /// Where the entity is right now, if it has a `Pos`
+    pub fn get_pos(&self, eid: Entity) -> Option<Pos> {
+        self.ctx.try_get_pos(eid)
+    }
+
+    /// How fast the entity is moving right now, if it has a `Dyno`
+    pub fn get_vel(&self, eid: Entity) -> Option<Vec2> {
+        self.ctx.try_get_vel(eid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::*, test_utils::*};
+
+    #[derive(Resource)]
+    struct Slide {
+        eid: Entity,
+        delta: Vec2,
+        moved: Option<Vec2>,
+    }
+
+    fn slide(
+        mut slide: ResMut<Slide>,
+        mut mover: MoverGeneric<TestTriggerRxKind, TestTriggerTxKind>,
+    ) {
+        let result = mover.move_and_slide(slide.eid, slide.delta);
+        assert_eq!(mover.get_pos(slide.eid).unwrap().x, result.moved.x);
+        slide.moved = Some(result.moved);
+    }
+
+    #[test]
+    fn slide_into_freshly_spawned_wall() {
+        let mut app = test_app();
+        let eid = app
+            .world_mut()
+            .spawn((
+                Pos::default(),
+                StaticRx::single(StaticRxKind::Default, HBox::new(10, 10)),
+            ))
+            .id();
+        // Not in the broadphase yet, physics hasn't run since it was spawned
+        app.world_mut().spawn((
+            Pos::new(30.0, 0.0),
+            StaticTx::single(StaticTxKind::Solid, HBox::new(10, 10)),
+        ));
+        app.insert_resource(Slide {
+            eid,
+            delta: Vec2::new(50.0, 0.0),
+            moved: None,
+        });
+        app.add_systems(Update, slide.before(PhysicsSet));
+        app.update();
+        let moved = app.world().resource::<Slide>().moved.unwrap();
+        assert!((moved.x - 20.0).abs() < 0.01, "moved {moved}");
+    }
+}
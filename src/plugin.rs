@@ -1,10 +1,13 @@
 use bevy::prelude::*;
 
 use crate::{
+    broadphase,
     bullet_time::{BulletTimeClassDefault, BulletTimePlugin},
-    colls, logic, pos,
+    colls,
+    config::PhysicsConfig,
+    logic, pos,
     prelude::BulletTimeClass,
-    triggers::TriggerKind,
+    triggers::{TriggerKind, TriggerRulesGeneric},
 };
 
 pub struct PhysicsPluginGeneric<
@@ -12,6 +15,8 @@ pub struct PhysicsPluginGeneric<
     TriggerTxKind: TriggerKind,
     TimeClass: BulletTimeClass = BulletTimeClassDefault,
 > {
+    config: PhysicsConfig,
+    trigger_rules: TriggerRulesGeneric<TriggerRxKind, TriggerTxKind>,
     _pd: std::marker::PhantomData<(TriggerRxKind, TriggerTxKind, TimeClass)>,
 }
 impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind, TimeClass: BulletTimeClass> Default
@@ -19,14 +24,34 @@ impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind, TimeClass: BulletTi
 {
     fn default() -> Self {
         Self {
+            config: default(),
+            trigger_rules: default(),
             _pd: std::marker::PhantomData,
         }
     }
 }
+impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind, TimeClass: BulletTimeClass>
+    PhysicsPluginGeneric<TriggerRxKind, TriggerTxKind, TimeClass>
+{
+    pub fn with_config(mut self, config: PhysicsConfig) -> Self {
+        self.config = config;
+        self
+    }
+    pub fn with_trigger_rules(
+        mut self,
+        trigger_rules: TriggerRulesGeneric<TriggerRxKind, TriggerTxKind>,
+    ) -> Self {
+        self.trigger_rules = trigger_rules;
+        self
+    }
+}
 impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind, TimeClass: BulletTimeClass> Plugin
     for PhysicsPluginGeneric<TriggerRxKind, TriggerTxKind, TimeClass>
 {
     fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone());
+        app.insert_resource(self.trigger_rules.clone());
+        broadphase::register_broadphase(app, self.config.broadphase_cell_size);
         colls::register_colls::<TriggerRxKind, TriggerTxKind>(app);
         logic::register_logic::<TriggerRxKind, TriggerTxKind, TimeClass>(app);
         pos::register_pos(app);
@@ -0,0 +1,489 @@
+//! Asking questions about the physics world without moving anything.
+//! Everything here looks straight at the ctrls, so works at any time (not just in `PhysicsSet`).
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    hbox::{HBox, HBoxMarker},
+    hcircle::HCircle,
+    hpoly::{get_convex_hull, raycast_convex},
+    hshape::HShape,
+    pos::Pos,
+    statics::{StaticRx, StaticRxKind, StaticTx, StaticTxKind},
+    triggers::{TriggerKind, TriggerRxGeneric, TriggerTxGeneric},
+};
+
+/// The kind of comp a query found, and which sort of ctrl it was on
+#[derive(Clone, Debug, Reflect)]
+pub enum CompKindGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    StaticRx(StaticRxKind),
+    StaticTx(StaticTxKind),
+    TriggerRx(TriggerRxKind),
+    TriggerTx(TriggerTxKind),
+}
+
+/// Decides which comps a query can find. By default, everything.
+#[allow(clippy::type_complexity)]
+pub struct QueryFilterGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    kinds:
+        Option<Box<dyn Fn(&CompKindGeneric<TriggerRxKind, TriggerTxKind>) -> bool + Send + Sync>>,
+    exclude: Vec<Entity>,
+}
+impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> Default
+    for QueryFilterGeneric<TriggerRxKind, TriggerTxKind>
+{
+    fn default() -> Self {
+        Self {
+            kinds: None,
+            exclude: vec![],
+        }
+    }
+}
+impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>
+    QueryFilterGeneric<TriggerRxKind, TriggerTxKind>
+{
+    /// Only find comps whose kind passes the given test. Replaces any earlier kind test.
+    pub fn with_kinds(
+        mut self,
+        kinds: impl Fn(&CompKindGeneric<TriggerRxKind, TriggerTxKind>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.kinds = Some(Box::new(kinds));
+        self
+    }
+    /// Only find static comps of the given kinds (and no trigger comps)
+    pub fn with_static_kinds<I: IntoIterator<Item = StaticTxKind>>(self, kinds: I) -> Self {
+        let kinds = kinds.into_iter().collect::<Vec<_>>();
+        self.with_kinds(move |kind| match kind {
+            CompKindGeneric::StaticTx(kind) => kinds.contains(kind),
+            _ => false,
+        })
+    }
+    /// Only find trigger comps whose kind passes the given test (and no static comps)
+    pub fn with_trigger_kinds(
+        self,
+        kinds: impl Fn(&TriggerTxKind) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.with_kinds(move |kind| match kind {
+            CompKindGeneric::TriggerTx(kind) => kinds(kind),
+            _ => false,
+        })
+    }
+    /// Never find anything on the given entity (like whoever is doing the looking)
+    pub fn excluding(mut self, eid: Entity) -> Self {
+        self.exclude.push(eid);
+        self
+    }
+
+    fn allows(&self, eid: Entity, kind: &CompKindGeneric<TriggerRxKind, TriggerTxKind>) -> bool {
+        !self.exclude.contains(&eid) && self.kinds.as_ref().is_none_or(|kinds| kinds(kind))
+    }
+}
+
+/// Where a ray hit a comp
+#[derive(Clone, Debug, Reflect)]
+pub struct RaycastHitGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    /// Entity of the control the comp is on
+    pub eid: Entity,
+    /// The kind of the comp that was hit
+    pub kind: CompKindGeneric<TriggerRxKind, TriggerTxKind>,
+    /// The marker of the hbox that was hit
+    pub marker: HBoxMarker,
+    /// Where the ray hit
+    pub point: Vec2,
+    /// The normal of the surface where the ray hit
+    pub normal: Vec2,
+    /// How far along the ray the hit is
+    pub dist: f32,
+}
+
+/// A comp found overlapping something
+#[derive(Clone, Debug, Reflect)]
+pub struct OverlapHitGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    /// Entity of the control the comp is on
+    pub eid: Entity,
+    /// The kind of the comp
+    pub kind: CompKindGeneric<TriggerRxKind, TriggerTxKind>,
+    /// The marker of the hbox
+    pub marker: HBoxMarker,
+}
+
+/// How to measure distances in queries
+#[derive(Clone, Copy, Debug, Default, Reflect, PartialEq, Eq)]
+pub enum DistanceMetric {
+    /// Straight line distance
+    #[default]
+    Euclidean,
+    /// Horizontal distance plus vertical distance
+    Manhattan,
+}
+
+/// A comp found near a point
+#[derive(Clone, Debug, Reflect)]
+pub struct NearbyHitGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    /// Entity of the control the comp is on
+    pub eid: Entity,
+    /// The kind of the comp
+    pub kind: CompKindGeneric<TriggerRxKind, TriggerTxKind>,
+    /// The marker of the hbox
+    pub marker: HBoxMarker,
+    /// Distance from the point to the closest part of the comp. Zero if the point is inside.
+    pub dist: f32,
+}
+
+/// Where a box moving through the static world would stop
+#[derive(Clone, Debug, Reflect)]
+pub struct ShapeCastHit {
+    /// Entity of the control the comp is on
+    pub eid: Entity,
+    /// The kind of the comp that was hit
+    pub kind: StaticTxKind,
+    /// The marker of the hbox that was hit
+    pub marker: HBoxMarker,
+    /// How far through the move the hit happens, from 0 (right away) to 1 (right at the end)
+    pub toi: f32,
+    /// Where the pos of the box would be when it stops
+    pub stop_pos: Vec2,
+    /// How far (along the normal) the box would be pushed back out if it tried to make the whole move
+    pub push: Vec2,
+    /// Normal of the surface that was hit
+    pub normal: Vec2,
+}
+
+/// Casts the box (already translated) along `dir` (normalized) at the shape. If it hits within
+/// `max_dist`, returns how far it gets before touching and the normal of the surface it touches.
+fn cast_box(thbox: &HBox, dir: Vec2, max_dist: f32, tx_thbox: &HShape) -> Option<(f32, Vec2)> {
+    // Shrink the box down to its center and grow the shape to match, then it's just a raycast
+    let center = thbox.get_offset();
+    let half = thbox.get_size().as_vec2() / 2.0;
+    let grow = |corners: &[Vec2]| {
+        let points = corners
+            .iter()
+            .flat_map(|corner| {
+                [
+                    *corner + Vec2::new(-half.x, -half.y),
+                    *corner + Vec2::new(half.x, -half.y),
+                    *corner + Vec2::new(half.x, half.y),
+                    *corner + Vec2::new(-half.x, half.y),
+                ]
+            })
+            .collect();
+        get_convex_hull(points)
+    };
+    match tx_thbox {
+        HShape::Box(hbox) => raycast_convex(&grow(&hbox.get_corners()), center, dir, max_dist),
+        HShape::Slope(slope) => raycast_convex(&grow(&slope.get_corners()), center, dir, max_dist),
+        HShape::Poly(poly) => raycast_convex(&grow(&poly.get_corners()), center, dir, max_dist),
+        HShape::Circle(circle) => {
+            // A box grown by a circle is a rounded rect, made of two crossed rects and four circles
+            let radius = circle.get_radius();
+            let offset = circle.get_offset();
+            let rects = [
+                Vec2::new(half.x + radius, half.y),
+                Vec2::new(half.x, half.y + radius),
+            ]
+            .map(|half| {
+                let rect = Rect::from_center_half_size(offset, half);
+                let corners = [
+                    rect.min,
+                    Vec2::new(rect.max.x, rect.min.y),
+                    rect.max,
+                    Vec2::new(rect.min.x, rect.max.y),
+                ];
+                raycast_convex(&corners, center, dir, max_dist)
+            });
+            let circles = [
+                Vec2::new(-half.x, -half.y),
+                Vec2::new(half.x, -half.y),
+                Vec2::new(half.x, half.y),
+                Vec2::new(-half.x, half.y),
+            ]
+            .map(|corner| {
+                HCircle::new(radius)
+                    .with_offset(offset.x + corner.x, offset.y + corner.y)
+                    .raycast(center, dir, max_dist)
+            });
+            rects
+                .into_iter()
+                .chain(circles)
+                .flatten()
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+        }
+    }
+}
+
+/// Asks questions about the static and trigger comps in the world without moving anything
+#[derive(SystemParam)]
+pub struct SpatialQueryGeneric<'w, 's, TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    pos_q: Query<'w, 's, &'static Pos>,
+    srx_q: Query<'w, 's, (Entity, &'static StaticRx)>,
+    stx_q: Query<'w, 's, (Entity, &'static StaticTx)>,
+    trx_q: Query<'w, 's, (Entity, &'static TriggerRxGeneric<TriggerRxKind>)>,
+    ttx_q: Query<'w, 's, (Entity, &'static TriggerTxGeneric<TriggerTxKind>)>,
+}
+impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>
+    SpatialQueryGeneric<'_, '_, TriggerRxKind, TriggerTxKind>
+{
+    /// Every tx comp (static and trigger) in the world, translated to where it is now
+    fn get_tx_comps(
+        &self,
+    ) -> Vec<(
+        Entity,
+        CompKindGeneric<TriggerRxKind, TriggerTxKind>,
+        HShape,
+    )> {
+        let mut result = vec![];
+        for (eid, stx) in &self.stx_q {
+            let pos = self.pos_q.get(eid).expect("Missing pos on stx");
+            for comp in &stx.comps {
+                let thbox = comp.shape.translated(pos.x, pos.y);
+                result.push((eid, CompKindGeneric::StaticTx(comp.kind), thbox));
+            }
+        }
+        for (eid, ttx) in &self.ttx_q {
+            let pos = self.pos_q.get(eid).expect("Missing pos on ttx");
+            for comp in &ttx.comps {
+                let thbox = comp.shape.translated(pos.x, pos.y);
+                result.push((eid, CompKindGeneric::TriggerTx(comp.kind.clone()), thbox));
+            }
+        }
+        result
+    }
+
+    /// Every comp (static and trigger, rx and tx) in the world, translated to where it is now
+    fn get_all_comps(
+        &self,
+    ) -> Vec<(
+        Entity,
+        CompKindGeneric<TriggerRxKind, TriggerTxKind>,
+        HShape,
+    )> {
+        let mut result = vec![];
+        for (eid, srx) in &self.srx_q {
+            let pos = self.pos_q.get(eid).expect("Missing pos on srx");
+            for comp in &srx.comps {
+                let thbox = comp.shape.translated(pos.x, pos.y);
+                result.push((eid, CompKindGeneric::StaticRx(comp.kind), thbox));
+            }
+        }
+        for (eid, trx) in &self.trx_q {
+            let pos = self.pos_q.get(eid).expect("Missing pos on trx");
+            for comp in &trx.comps {
+                let thbox = comp.shape.translated(pos.x, pos.y);
+                result.push((eid, CompKindGeneric::TriggerRx(comp.kind.clone()), thbox));
+            }
+        }
+        result.extend(self.get_tx_comps());
+        result
+    }
+
+    /// Every comp (static and trigger, rx and tx) that passes the test, sorted by entity
+    fn get_overlapping(
+        &self,
+        filter: &QueryFilterGeneric<TriggerRxKind, TriggerTxKind>,
+        overlaps: impl Fn(&HShape) -> bool,
+    ) -> Vec<OverlapHitGeneric<TriggerRxKind, TriggerTxKind>> {
+        let mut hits = self
+            .get_all_comps()
+            .into_iter()
+            .filter(|(eid, kind, thbox)| filter.allows(*eid, kind) && overlaps(thbox))
+            .map(|(eid, kind, thbox)| OverlapHitGeneric {
+                eid,
+                kind,
+                marker: thbox.get_marker(),
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by_key(|hit| hit.eid);
+        hits
+    }
+
+    /// Every comp (static and trigger, rx and tx) containing the point. Being on the edge counts.
+    pub fn overlapping_point(
+        &self,
+        point: Vec2,
+        filter: &QueryFilterGeneric<TriggerRxKind, TriggerTxKind>,
+    ) -> Vec<OverlapHitGeneric<TriggerRxKind, TriggerTxKind>> {
+        self.get_overlapping(filter, |thbox| thbox.contains_point(point))
+    }
+
+    /// Every comp (static and trigger, rx and tx) overlapping the region.
+    /// The region's offset is its center in the world. Just touching doesn't count.
+    pub fn overlapping_region(
+        &self,
+        region: &HBox,
+        filter: &QueryFilterGeneric<TriggerRxKind, TriggerTxKind>,
+    ) -> Vec<OverlapHitGeneric<TriggerRxKind, TriggerTxKind>> {
+        let region = HShape::Box(region.clone());
+        self.get_overlapping(filter, |thbox| region.overlaps_with(thbox))
+    }
+
+    /// Every comp (static and trigger, rx and tx) with its distance to the point, closest first
+    fn get_by_distance(
+        &self,
+        point: Vec2,
+        metric: DistanceMetric,
+        filter: &QueryFilterGeneric<TriggerRxKind, TriggerTxKind>,
+    ) -> Vec<NearbyHitGeneric<TriggerRxKind, TriggerTxKind>> {
+        let mut hits = self
+            .get_all_comps()
+            .into_iter()
+            .filter(|(eid, kind, _)| filter.allows(*eid, kind))
+            .map(|(eid, kind, thbox)| NearbyHitGeneric {
+                eid,
+                kind,
+                marker: thbox.get_marker(),
+                dist: match metric {
+                    DistanceMetric::Euclidean => thbox.euclidean_distance_to_point(point),
+                    DistanceMetric::Manhattan => thbox.manhattan_distance_to_point(point),
+                },
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| a.dist.total_cmp(&b.dist).then(a.eid.cmp(&b.eid)));
+        hits
+    }
+
+    /// The (up to) `num` comps (static and trigger, rx and tx) closest to the point, closest first
+    pub fn nearest(
+        &self,
+        point: Vec2,
+        num: usize,
+        metric: DistanceMetric,
+        filter: &QueryFilterGeneric<TriggerRxKind, TriggerTxKind>,
+    ) -> Vec<NearbyHitGeneric<TriggerRxKind, TriggerTxKind>> {
+        let mut hits = self.get_by_distance(point, metric, filter);
+        hits.truncate(num);
+        hits
+    }
+
+    /// Every comp (static and trigger, rx and tx) no further than `radius` from the point,
+    /// closest first
+    pub fn within_radius(
+        &self,
+        point: Vec2,
+        radius: f32,
+        metric: DistanceMetric,
+        filter: &QueryFilterGeneric<TriggerRxKind, TriggerTxKind>,
+    ) -> Vec<NearbyHitGeneric<TriggerRxKind, TriggerTxKind>> {
+        let mut hits = self.get_by_distance(point, metric, filter);
+        hits.retain(|hit| hit.dist <= radius);
+        hits
+    }
+
+    /// Every static and trigger tx comp the ray hits within `max_dist`, closest first
+    pub fn raycast_all(
+        &self,
+        origin: Vec2,
+        dir: Vec2,
+        max_dist: f32,
+        filter: &QueryFilterGeneric<TriggerRxKind, TriggerTxKind>,
+    ) -> Vec<RaycastHitGeneric<TriggerRxKind, TriggerTxKind>> {
+        let Some(dir) = dir.try_normalize() else {
+            return vec![];
+        };
+        // Rays are often perfectly flat, so touching counts here
+        let ray_rect = Rect::from_corners(origin, origin + dir * max_dist);
+        let might_hit = |thbox: &HShape| {
+            let bounds = thbox.get_bounds();
+            ray_rect.min.cmple(bounds.max).all() && bounds.min.cmple(ray_rect.max).all()
+        };
+        let mut hits = self
+            .get_tx_comps()
+            .into_iter()
+            .filter(|(eid, kind, _)| filter.allows(*eid, kind))
+            .filter(|(_, _, thbox)| might_hit(thbox))
+            .filter_map(|(eid, kind, thbox)| {
+                let (dist, normal) = thbox.raycast(origin, dir, max_dist)?;
+                Some(RaycastHitGeneric {
+                    eid,
+                    kind,
+                    marker: thbox.get_marker(),
+                    point: origin + dir * dist,
+                    normal,
+                    dist,
+                })
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| a.dist.total_cmp(&b.dist).then(a.eid.cmp(&b.eid)));
+        hits
+    }
+
+    /// Where the hbox (relative to `origin`, like on a ctrl at that pos) would stop if it moved by
+    /// `motion` through the static world. None if the whole move is clear.
+    /// Like real movement, one-way platforms only stop it moving down from above them.
+    /// NOTE: Just asks, doesn't move anything or record any collisions
+    pub fn shape_cast(
+        &self,
+        hbox: &HBox,
+        origin: Vec2,
+        motion: Vec2,
+        filter: &QueryFilterGeneric<TriggerRxKind, TriggerTxKind>,
+    ) -> Option<ShapeCastHit> {
+        let max_dist = motion.length();
+        let dir = motion.try_normalize()?;
+        let thbox = hbox.translated(origin.x, origin.y);
+        let my_shape = HShape::Box(thbox.clone());
+        let swept_rect = my_shape
+            .translated(motion.x, motion.y)
+            .get_swept_rect(motion);
+        let mut best: Option<ShapeCastHit> = None;
+        for (eid, kind, tx_thbox) in self.get_tx_comps() {
+            let CompKindGeneric::StaticTx(static_kind) = kind else {
+                continue;
+            };
+            if !filter.allows(eid, &kind) {
+                continue;
+            }
+            let bounds = tx_thbox.get_bounds();
+            if !(swept_rect.min.cmple(bounds.max).all() && bounds.min.cmple(swept_rect.max).all()) {
+                continue;
+            }
+            if static_kind == StaticTxKind::PassUp
+                && (dir.y >= 0.0 || thbox.min_y() < tx_thbox.max_y())
+            {
+                continue;
+            }
+            let Some((dist, mut normal)) = cast_box(&thbox, dir, max_dist, &tx_thbox) else {
+                continue;
+            };
+            if dist == 0.0 {
+                // Already stuck in it, so report the way out like resolving would
+                if let Some((_, push_normal)) = my_shape.get_push_out_n_normal(&tx_thbox) {
+                    normal = push_normal;
+                }
+            }
+            if static_kind == StaticTxKind::PassUp {
+                normal = Vec2::Y;
+            }
+            let toi = dist / max_dist;
+            if best
+                .as_ref()
+                .is_some_and(|best| (best.toi, best.eid) <= (toi, eid))
+            {
+                continue;
+            }
+            let remaining = motion * (1.0 - toi);
+            best = Some(ShapeCastHit {
+                eid,
+                kind: static_kind,
+                marker: tx_thbox.get_marker(),
+                toi,
+                stop_pos: origin + motion * toi,
+                push: normal * -remaining.dot(normal).min(0.0),
+                normal,
+            });
+        }
+        best
+    }
+
+    /// The closest static or trigger tx comp the ray hits within `max_dist`, if any
+    pub fn raycast(
+        &self,
+        origin: Vec2,
+        dir: Vec2,
+        max_dist: f32,
+        filter: &QueryFilterGeneric<TriggerRxKind, TriggerTxKind>,
+    ) -> Option<RaycastHitGeneric<TriggerRxKind, TriggerTxKind>> {
+        self.raycast_all(origin, dir, max_dist, filter)
+            .into_iter()
+            .next()
+    }
+}
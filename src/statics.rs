@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
-use crate::{colls::CollKey, hbox::HBox, pos::Pos};
+use crate::{
+    colls::CollKey, hbox::HBox, hshape::HShape, layers::CollLayers, material::PhysicsMaterial,
+    pos::Pos,
+};
 
 #[derive(Clone, Copy, Debug, Reflect, PartialEq, Eq, std::hash::Hash)]
 pub enum StaticRxKind {
@@ -13,35 +16,102 @@ pub enum StaticRxKind {
 pub enum StaticTxKind {
     /// Standard solid thing. Stops stuff
     Solid,
+    /// One-way platform. Only stops rx ctrls falling onto it from above, never pushes sideways or down
+    PassUp,
 }
 
-pub(crate) struct StaticRxComp {
+pub struct StaticRxComp {
     pub(crate) kind: StaticRxKind,
-    pub(crate) hbox: HBox,
+    pub(crate) shape: HShape,
+    pub(crate) layers: CollLayers,
+}
+impl StaticRxComp {
+    pub fn new<S: Into<HShape>>(kind: StaticRxKind, shape: S) -> Self {
+        Self {
+            kind,
+            shape: shape.into(),
+            layers: default(),
+        }
+    }
+    pub fn with_layers(mut self, layers: CollLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+}
+impl<S: Into<HShape>> From<(StaticRxKind, S)> for StaticRxComp {
+    fn from((kind, shape): (StaticRxKind, S)) -> Self {
+        Self::new(kind, shape)
+    }
 }
 #[derive(Component)]
 pub struct StaticRx {
     pub(crate) comps: Vec<StaticRxComp>,
     pub coll_keys: Vec<CollKey>,
+    /// Keys into `StaticCrushes` for any time this was squeezed between solids this frame
+    pub crush_keys: Vec<CollKey>,
 }
 impl StaticRx {
-    pub fn single(kind: StaticRxKind, hbox: HBox) -> Self {
-        Self::new(vec![(kind, hbox)])
+    pub fn single<S: Into<HShape>>(kind: StaticRxKind, shape: S) -> Self {
+        Self::new([StaticRxComp::new(kind, shape)])
     }
-    pub fn new<I: IntoIterator<Item = (StaticRxKind, HBox)>>(data: I) -> Self {
+    /// Takes comps, or `(kind, shape)` tuples
+    pub fn new<C: Into<StaticRxComp>, I: IntoIterator<Item = C>>(data: I) -> Self {
         Self {
-            comps: data
-                .into_iter()
-                .map(|(kind, hbox)| StaticRxComp { kind, hbox })
-                .collect(),
+            comps: data.into_iter().map(Into::into).collect(),
             coll_keys: vec![],
+            crush_keys: vec![],
         }
     }
+    /// Puts every comp on the given layers
+    pub fn with_layers(mut self, layers: CollLayers) -> Self {
+        for comp in self.comps.iter_mut() {
+            comp.layers = layers;
+        }
+        self
+    }
 }
 
-pub(crate) struct StaticTxComp {
+/// Marks a dynamic solid (an entity with both a `StaticRx` and a `StaticTx`) that can be shoved
+/// around by other rx ctrls moving into it. Without this, dynamic solids just block like walls.
+#[derive(Component, Clone, Debug, Reflect, Default)]
+pub struct Pushable;
+
+pub struct StaticTxComp {
     pub(crate) kind: StaticTxKind,
-    pub(crate) hbox: HBox,
+    pub(crate) shape: HShape,
+    pub(crate) layers: CollLayers,
+    pub(crate) material: PhysicsMaterial,
+    pub(crate) surface_vel: Vec2,
+}
+impl StaticTxComp {
+    pub fn new<S: Into<HShape>>(kind: StaticTxKind, shape: S) -> Self {
+        Self {
+            kind,
+            shape: shape.into(),
+            layers: default(),
+            material: default(),
+            surface_vel: default(),
+        }
+    }
+    pub fn with_layers(mut self, layers: CollLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+    pub fn with_material(mut self, material: PhysicsMaterial) -> Self {
+        self.material = material;
+        self
+    }
+    /// Makes the surface move (like a conveyor belt) without the comp itself going anywhere.
+    /// Rxs pushed out of it are carried along the contact tangent by this much per second.
+    pub fn with_surface_vel(mut self, surface_vel: Vec2) -> Self {
+        self.surface_vel = surface_vel;
+        self
+    }
+}
+impl<S: Into<HShape>> From<(StaticTxKind, S)> for StaticTxComp {
+    fn from((kind, shape): (StaticTxKind, S)) -> Self {
+        Self::new(kind, shape)
+    }
 }
 #[derive(Component)]
 pub struct StaticTx {
@@ -49,22 +119,54 @@ pub struct StaticTx {
     pub coll_keys: Vec<CollKey>,
 }
 impl StaticTx {
-    pub fn single(kind: StaticTxKind, hbox: HBox) -> Self {
-        Self::new(vec![(kind, hbox)])
+    pub fn single<S: Into<HShape>>(kind: StaticTxKind, shape: S) -> Self {
+        Self::new([StaticTxComp::new(kind, shape)])
     }
-    pub fn new<I: IntoIterator<Item = (StaticTxKind, HBox)>>(data: I) -> Self {
+    /// Takes comps, or `(kind, shape)` tuples
+    pub fn new<C: Into<StaticTxComp>, I: IntoIterator<Item = C>>(data: I) -> Self {
         Self {
-            comps: data
-                .into_iter()
-                .map(|(kind, hbox)| StaticTxComp { kind, hbox })
-                .collect(),
+            comps: data.into_iter().map(Into::into).collect(),
             coll_keys: vec![],
         }
     }
+    /// Puts every comp on the given layers
+    pub fn with_layers(mut self, layers: CollLayers) -> Self {
+        for comp in self.comps.iter_mut() {
+            comp.layers = layers;
+        }
+        self
+    }
+    /// Gives every comp the given material
+    pub fn with_material(mut self, material: PhysicsMaterial) -> Self {
+        for comp in self.comps.iter_mut() {
+            comp.material = material;
+        }
+        self
+    }
+    /// Gives every comp the given surface velocity
+    pub fn with_surface_vel(mut self, surface_vel: Vec2) -> Self {
+        for comp in self.comps.iter_mut() {
+            comp.surface_vel = surface_vel;
+        }
+        self
+    }
+    /// The translated hbox of every comp that's a box.
+    /// NOTE: Only returns `HBox` comps, circles, slopes and polys are skipped
+    #[deprecated(note = "skips every comp that isn't an `HBox`, use `get_thshapes` instead")]
     pub fn get_thboxes(&self, pos: Pos) -> Vec<HBox> {
         self.comps
             .iter()
-            .map(|comp| comp.hbox.translated(pos.x, pos.y))
+            .filter_map(|comp| match &comp.shape {
+                HShape::Box(hbox) => Some(hbox.translated(pos.x, pos.y)),
+                _ => None,
+            })
+            .collect()
+    }
+    /// The translated shape of every comp
+    pub fn get_thshapes(&self, pos: Pos) -> Vec<HShape> {
+        self.comps
+            .iter()
+            .map(|comp| comp.shape.translated(pos.x, pos.y))
             .collect()
     }
 }
@@ -0,0 +1,49 @@
+//! Helpers for tests that need to run a whole app through a few physics frames
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::prelude::*;
+
+#[derive(std::hash::Hash, Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TestTriggerRxKind {
+    Player,
+}
+impl TriggerKind for TestTriggerRxKind {}
+
+#[derive(std::hash::Hash, Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TestTriggerTxKind {
+    Spikes,
+}
+impl TriggerKind for TestTriggerTxKind {}
+
+pub(crate) type TriggerRx = TriggerRxGeneric<TestTriggerRxKind>;
+pub(crate) type TriggerTx = TriggerTxGeneric<TestTriggerTxKind>;
+pub(crate) type TriggerColls = TriggerCollsGeneric<TestTriggerRxKind, TestTriggerTxKind>;
+pub(crate) type TriggerExits = TriggerExitsGeneric<TestTriggerRxKind, TestTriggerTxKind>;
+
+/// Seconds that pass each update
+pub(crate) const DT: f32 = 1.0 / 60.0;
+
+/// An app with the physics plugin that steps exactly `DT` each update
+pub(crate) fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        std::time::Duration::from_secs_f32(DT),
+    ));
+    app.add_plugins(PhysicsPluginGeneric::<TestTriggerRxKind, TestTriggerTxKind>::default());
+    // The first update has no delta, get it out of the way
+    app.update();
+    app
+}
+
+pub(crate) fn get_pos(app: &App, eid: Entity) -> Pos {
+    *app.world().get::<Pos>(eid).expect("Missing pos")
+}
+
+/// Pulls everything with a static rx down
+pub(crate) fn gravity(mut dyno_q: Query<&mut Dyno, With<StaticRx>>) {
+    for mut dyno in &mut dyno_q {
+        dyno.vel.y -= 600.0 * DT;
+    }
+}
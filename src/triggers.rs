@@ -1,15 +1,38 @@
+use std::sync::Arc;
+
 use bevy::prelude::*;
 
-use crate::{colls::CollKey, hbox::HBox};
+use crate::{colls::CollKey, hshape::HShape, layers::CollLayers};
 
 pub trait TriggerKind:
     Clone + std::fmt::Debug + std::hash::Hash + std::marker::Send + std::marker::Sync + 'static
 {
 }
 
-pub(crate) struct TriggerRxComp<TriggerRxKind: TriggerKind> {
+pub struct TriggerRxComp<TriggerRxKind: TriggerKind> {
     pub(crate) kind: TriggerRxKind,
-    pub(crate) hbox: HBox,
+    pub(crate) shape: HShape,
+    pub(crate) layers: CollLayers,
+}
+impl<TriggerRxKind: TriggerKind> TriggerRxComp<TriggerRxKind> {
+    pub fn new<S: Into<HShape>>(kind: TriggerRxKind, shape: S) -> Self {
+        Self {
+            kind,
+            shape: shape.into(),
+            layers: default(),
+        }
+    }
+    pub fn with_layers(mut self, layers: CollLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+}
+impl<TriggerRxKind: TriggerKind, S: Into<HShape>> From<(TriggerRxKind, S)>
+    for TriggerRxComp<TriggerRxKind>
+{
+    fn from((kind, shape): (TriggerRxKind, S)) -> Self {
+        Self::new(kind, shape)
+    }
 }
 #[derive(Component)]
 pub struct TriggerRxGeneric<TriggerRxKind: TriggerKind> {
@@ -17,23 +40,49 @@ pub struct TriggerRxGeneric<TriggerRxKind: TriggerKind> {
     pub coll_keys: Vec<CollKey>,
 }
 impl<TriggerRxKind: TriggerKind> TriggerRxGeneric<TriggerRxKind> {
-    pub fn single(kind: TriggerRxKind, hbox: HBox) -> Self {
-        Self::new(vec![(kind, hbox)])
+    pub fn single<S: Into<HShape>>(kind: TriggerRxKind, shape: S) -> Self {
+        Self::new([TriggerRxComp::new(kind, shape)])
     }
-    pub fn new<I: IntoIterator<Item = (TriggerRxKind, HBox)>>(data: I) -> Self {
+    /// Takes comps, or `(kind, shape)` tuples
+    pub fn new<C: Into<TriggerRxComp<TriggerRxKind>>, I: IntoIterator<Item = C>>(data: I) -> Self {
         Self {
-            comps: data
-                .into_iter()
-                .map(|(kind, hbox)| TriggerRxComp { kind, hbox })
-                .collect(),
+            comps: data.into_iter().map(Into::into).collect(),
             coll_keys: vec![],
         }
     }
+    /// Puts every comp on the given layers
+    pub fn with_layers(mut self, layers: CollLayers) -> Self {
+        for comp in self.comps.iter_mut() {
+            comp.layers = layers;
+        }
+        self
+    }
 }
 
-pub(crate) struct TriggerTxComp<TriggerTxKind: TriggerKind> {
+pub struct TriggerTxComp<TriggerTxKind: TriggerKind> {
     pub(crate) kind: TriggerTxKind,
-    pub(crate) hbox: HBox,
+    pub(crate) shape: HShape,
+    pub(crate) layers: CollLayers,
+}
+impl<TriggerTxKind: TriggerKind> TriggerTxComp<TriggerTxKind> {
+    pub fn new<S: Into<HShape>>(kind: TriggerTxKind, shape: S) -> Self {
+        Self {
+            kind,
+            shape: shape.into(),
+            layers: default(),
+        }
+    }
+    pub fn with_layers(mut self, layers: CollLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+}
+impl<TriggerTxKind: TriggerKind, S: Into<HShape>> From<(TriggerTxKind, S)>
+    for TriggerTxComp<TriggerTxKind>
+{
+    fn from((kind, shape): (TriggerTxKind, S)) -> Self {
+        Self::new(kind, shape)
+    }
 }
 #[derive(Component)]
 pub struct TriggerTxGeneric<TriggerTxKind: TriggerKind> {
@@ -41,16 +90,75 @@ pub struct TriggerTxGeneric<TriggerTxKind: TriggerKind> {
     pub coll_keys: Vec<CollKey>,
 }
 impl<TriggerTxKind: TriggerKind> TriggerTxGeneric<TriggerTxKind> {
-    pub fn single(kind: TriggerTxKind, hbox: HBox) -> Self {
-        Self::new(vec![(kind, hbox)])
+    pub fn single<S: Into<HShape>>(kind: TriggerTxKind, shape: S) -> Self {
+        Self::new([TriggerTxComp::new(kind, shape)])
     }
-    pub fn new<I: IntoIterator<Item = (TriggerTxKind, HBox)>>(data: I) -> Self {
+    /// Takes comps, or `(kind, shape)` tuples
+    pub fn new<C: Into<TriggerTxComp<TriggerTxKind>>, I: IntoIterator<Item = C>>(data: I) -> Self {
         Self {
-            comps: data
-                .into_iter()
-                .map(|(kind, hbox)| TriggerTxComp { kind, hbox })
-                .collect(),
+            comps: data.into_iter().map(Into::into).collect(),
             coll_keys: vec![],
         }
     }
+    /// Puts every comp on the given layers
+    pub fn with_layers(mut self, layers: CollLayers) -> Self {
+        for comp in self.comps.iter_mut() {
+            comp.layers = layers;
+        }
+        self
+    }
+}
+
+/// Says which trigger rx kinds interact with which trigger tx kinds. Pairs that don't interact are
+/// skipped before any overlap test, and never recorded. By default everything interacts.
+/// Register it with `PhysicsPluginGeneric::with_trigger_rules`, or change the resource at runtime.
+#[allow(clippy::type_complexity)]
+#[derive(Resource)]
+pub struct TriggerRulesGeneric<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> {
+    interacts: Arc<dyn Fn(&TriggerRxKind, &TriggerTxKind) -> bool + Send + Sync>,
+}
+impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> Clone
+    for TriggerRulesGeneric<TriggerRxKind, TriggerTxKind>
+{
+    fn clone(&self) -> Self {
+        Self {
+            interacts: self.interacts.clone(),
+        }
+    }
+}
+impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind> Default
+    for TriggerRulesGeneric<TriggerRxKind, TriggerTxKind>
+{
+    fn default() -> Self {
+        Self::from_fn(|_, _| true)
+    }
+}
+impl<TriggerRxKind: TriggerKind, TriggerTxKind: TriggerKind>
+    TriggerRulesGeneric<TriggerRxKind, TriggerTxKind>
+{
+    /// Rules decided by any function of the two kinds
+    pub fn from_fn(
+        interacts: impl Fn(&TriggerRxKind, &TriggerTxKind) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            interacts: Arc::new(interacts),
+        }
+    }
+    /// Rules where only the listed `(rx kind, tx kind)` pairs interact
+    pub fn from_pairs<I: IntoIterator<Item = (TriggerRxKind, TriggerTxKind)>>(pairs: I) -> Self
+    where
+        TriggerRxKind: PartialEq,
+        TriggerTxKind: PartialEq,
+    {
+        let pairs = pairs.into_iter().collect::<Vec<_>>();
+        Self::from_fn(move |rx_kind, tx_kind| {
+            pairs
+                .iter()
+                .any(|(rule_rx, rule_tx)| rule_rx == rx_kind && rule_tx == tx_kind)
+        })
+    }
+    /// Returns if comps of these kinds should ever trigger each other
+    pub fn interacts(&self, rx_kind: &TriggerRxKind, tx_kind: &TriggerTxKind) -> bool {
+        (self.interacts)(rx_kind, tx_kind)
+    }
 }